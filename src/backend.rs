@@ -0,0 +1,307 @@
+use {
+    crate::{
+        navigation::{Ancestors, BreadthFirst, Children, Descendants, FollowingSiblings, Walker},
+        ControlType, Property, PropertyConditionFlags, PropertyValue, TreeScope, UiacResult, View,
+    },
+    std::rc::Rc,
+};
+
+/// An entry point into a UIA tree, such as the Windows COM API.
+pub trait Automation {
+    type Element: Element<Condition = Self::Condition>;
+    type TreeWalker: TreeWalker<Element = Self::Element, CacheRequest = Self::CacheRequest>;
+    type Condition;
+    type CacheRequest;
+
+    fn get_root_element(&self) -> UiacResult<Self::Element>;
+    fn get_focused_element(&self) -> UiacResult<Self::Element>;
+    /// Finds the element at a point on the screen, in physical pixels.
+    fn get_element_from_point(&self, x: i32, y: i32) -> UiacResult<Self::Element>;
+    /// Whether two elements refer to the same node of the tree.
+    fn compare_elements(
+        &self,
+        element1: &Self::Element,
+        element2: &Self::Element,
+    ) -> UiacResult<bool>;
+    fn create_tree_walker(&self, condition: &Self::Condition) -> UiacResult<Self::TreeWalker>;
+    /// Creates a request for the given properties to be fetched along with
+    /// each element found by one of a walker's `_build_cache` methods, which
+    /// saves a cross-process call per property when walking a live tree.
+    fn create_cache_request(&self, properties: &[Property]) -> UiacResult<Self::CacheRequest>;
+    fn create_true_condition(&self) -> UiacResult<Self::Condition>;
+    fn create_false_condition(&self) -> UiacResult<Self::Condition>;
+
+    fn create_property_condition(
+        &self,
+        property: Property,
+        value: &PropertyValue,
+    ) -> UiacResult<Self::Condition> {
+        self.create_property_condition_ex(property, value, PropertyConditionFlags::default())
+    }
+
+    /// Like `create_property_condition`, but `flags` control how string values
+    /// are compared.
+    fn create_property_condition_ex(
+        &self,
+        property: Property,
+        value: &PropertyValue,
+        flags: PropertyConditionFlags,
+    ) -> UiacResult<Self::Condition>;
+    fn create_and_condition(
+        &self,
+        condition1: &Self::Condition,
+        condition2: &Self::Condition,
+    ) -> UiacResult<Self::Condition>;
+    fn create_or_condition(
+        &self,
+        condition1: &Self::Condition,
+        condition2: &Self::Condition,
+    ) -> UiacResult<Self::Condition>;
+    fn create_not_condition(&self, condition: &Self::Condition) -> UiacResult<Self::Condition>;
+
+    /// Matches every element, so a walker using it sees the raw view.
+    fn get_raw_view_condition(&self) -> UiacResult<Self::Condition>;
+    /// Matches the elements in the control view, i.e. those that are
+    /// interactive or carry information, as screen readers see them.
+    fn get_control_view_condition(&self) -> UiacResult<Self::Condition>;
+    /// Matches the elements in the content view, a subset of the control view
+    /// without labels and decoration.
+    fn get_content_view_condition(&self) -> UiacResult<Self::Condition>;
+}
+
+/// A single node in a UIA tree.
+pub trait Element: Clone {
+    type Condition;
+
+    fn get_current_property_value(&self, property: Property) -> UiacResult<PropertyValue>;
+    /// Reads a property as it was when the element was fetched. This fails
+    /// unless the element was fetched with a cache request for the property.
+    fn get_cached_property_value(&self, property: Property) -> UiacResult<PropertyValue>;
+
+    /// Finds the first element in `scope`, in depth-first order, that matches
+    /// `condition`.
+    fn find_first(&self, scope: TreeScope, condition: &Self::Condition)
+        -> UiacResult<Option<Self>>;
+    /// Finds every element in `scope` that matches `condition`, in depth-first
+    /// order.
+    fn find_all(&self, scope: TreeScope, condition: &Self::Condition) -> UiacResult<Vec<Self>>;
+
+    fn get_current_name(&self) -> UiacResult<String> {
+        self.get_current_property_value(Property::Name)?
+            .into_string()
+    }
+
+    fn get_current_control_type(&self) -> UiacResult<ControlType> {
+        self.get_current_property_value(Property::ControlType)?
+            .as_control_type()
+    }
+
+    fn get_cached_name(&self) -> UiacResult<String> {
+        self.get_cached_property_value(Property::Name)?
+            .into_string()
+    }
+
+    fn get_cached_control_type(&self) -> UiacResult<ControlType> {
+        self.get_cached_property_value(Property::ControlType)?
+            .as_control_type()
+    }
+
+    /// Iterates over the element's children, as `walker` sees them. See
+    /// `TreeWalker::children`.
+    fn children<'a, W: TreeWalker<Element = Self>>(&self, walker: &'a W) -> Children<'a, W> {
+        walker.children(self)
+    }
+
+    /// Iterates over the element's children, in `view`.
+    fn children_in<'a, A: Automation<Element = Self>>(
+        &self,
+        automation: &A,
+        view: View,
+    ) -> UiacResult<Children<'a, A::TreeWalker>>
+    where
+        A::TreeWalker: 'a,
+    {
+        let walker = Walker::Shared(Rc::new(view.walker(automation)?));
+        Ok(Children::new(walker, self))
+    }
+
+    /// Iterates over the element's descendants in depth-first order, as
+    /// `walker` sees them. See `TreeWalker::descendants`.
+    fn descendants<'a, W: TreeWalker<Element = Self>>(&self, walker: &'a W) -> Descendants<'a, W> {
+        walker.descendants(self)
+    }
+
+    /// Iterates over the element's descendants in depth-first order, in `view`.
+    fn descendants_in<'a, A: Automation<Element = Self>>(
+        &self,
+        automation: &A,
+        view: View,
+    ) -> UiacResult<Descendants<'a, A::TreeWalker>>
+    where
+        A::TreeWalker: 'a,
+    {
+        let walker = Walker::Shared(Rc::new(view.walker(automation)?));
+        Ok(Descendants::new(walker, self))
+    }
+
+    /// Iterates over the element's descendants level by level, as `walker`
+    /// sees them. See `TreeWalker::descendants_breadth_first`.
+    fn descendants_breadth_first<'a, W: TreeWalker<Element = Self>>(
+        &self,
+        walker: &'a W,
+    ) -> BreadthFirst<'a, W> {
+        walker.descendants_breadth_first(self)
+    }
+
+    /// Iterates over the element's descendants level by level, in `view`.
+    fn descendants_breadth_first_in<'a, A: Automation<Element = Self>>(
+        &self,
+        automation: &A,
+        view: View,
+    ) -> UiacResult<BreadthFirst<'a, A::TreeWalker>>
+    where
+        A::TreeWalker: 'a,
+    {
+        let walker = Walker::Shared(Rc::new(view.walker(automation)?));
+        Ok(BreadthFirst::new(walker, self))
+    }
+
+    /// Iterates over the element's ancestors, from its parent up to the root,
+    /// as `walker` sees them. See `TreeWalker::ancestors`.
+    fn ancestors<'a, W: TreeWalker<Element = Self>>(&self, walker: &'a W) -> Ancestors<'a, W> {
+        walker.ancestors(self)
+    }
+
+    /// Iterates over the element's ancestors, from its parent up to the root,
+    /// in `view`.
+    fn ancestors_in<'a, A: Automation<Element = Self>>(
+        &self,
+        automation: &A,
+        view: View,
+    ) -> UiacResult<Ancestors<'a, A::TreeWalker>>
+    where
+        A::TreeWalker: 'a,
+    {
+        let walker = Walker::Shared(Rc::new(view.walker(automation)?));
+        Ok(Ancestors::new(walker, self))
+    }
+
+    /// Iterates over the siblings after the element, in order, as `walker`
+    /// sees them. See `TreeWalker::following_siblings`.
+    fn following_siblings<'a, W: TreeWalker<Element = Self>>(
+        &self,
+        walker: &'a W,
+    ) -> FollowingSiblings<'a, W> {
+        walker.following_siblings(self)
+    }
+
+    /// Iterates over the siblings after the element, in order, in `view`.
+    fn following_siblings_in<'a, A: Automation<Element = Self>>(
+        &self,
+        automation: &A,
+        view: View,
+    ) -> UiacResult<FollowingSiblings<'a, A::TreeWalker>>
+    where
+        A::TreeWalker: 'a,
+    {
+        let walker = Walker::Shared(Rc::new(view.walker(automation)?));
+        Ok(FollowingSiblings::new(walker, self))
+    }
+}
+
+/// Navigates between the elements of a UIA tree.
+pub trait TreeWalker {
+    type Element: Element;
+    type CacheRequest;
+
+    /// The nearest ancestor in the walker's view, or `None` for the root.
+    fn get_parent_element(&self, element: &Self::Element) -> UiacResult<Option<Self::Element>>;
+    fn get_first_child_element(&self, element: &Self::Element)
+        -> UiacResult<Option<Self::Element>>;
+    fn get_last_child_element(&self, element: &Self::Element) -> UiacResult<Option<Self::Element>>;
+    fn get_next_sibling_element(
+        &self,
+        element: &Self::Element,
+    ) -> UiacResult<Option<Self::Element>>;
+    fn get_previous_sibling_element(
+        &self,
+        element: &Self::Element,
+    ) -> UiacResult<Option<Self::Element>>;
+    /// The element itself if it's in the walker's view, or else its nearest
+    /// ancestor that is.
+    fn normalize_element(&self, element: &Self::Element) -> UiacResult<Option<Self::Element>>;
+
+    // The same, but fetching the element with `cache_request`.
+    fn get_parent_element_build_cache(
+        &self,
+        element: &Self::Element,
+        cache_request: &Self::CacheRequest,
+    ) -> UiacResult<Option<Self::Element>>;
+    fn get_first_child_element_build_cache(
+        &self,
+        element: &Self::Element,
+        cache_request: &Self::CacheRequest,
+    ) -> UiacResult<Option<Self::Element>>;
+    fn get_last_child_element_build_cache(
+        &self,
+        element: &Self::Element,
+        cache_request: &Self::CacheRequest,
+    ) -> UiacResult<Option<Self::Element>>;
+    fn get_next_sibling_element_build_cache(
+        &self,
+        element: &Self::Element,
+        cache_request: &Self::CacheRequest,
+    ) -> UiacResult<Option<Self::Element>>;
+    fn get_previous_sibling_element_build_cache(
+        &self,
+        element: &Self::Element,
+        cache_request: &Self::CacheRequest,
+    ) -> UiacResult<Option<Self::Element>>;
+    fn normalize_element_build_cache(
+        &self,
+        element: &Self::Element,
+        cache_request: &Self::CacheRequest,
+    ) -> UiacResult<Option<Self::Element>>;
+
+    /// Iterates over the children of `element`.
+    fn children(&self, element: &Self::Element) -> Children<'_, Self>
+    where
+        Self: Sized,
+    {
+        Children::new(Walker::Borrowed(self), element)
+    }
+
+    /// Iterates over the descendants of `element` in depth-first order, each
+    /// before its own descendants.
+    fn descendants(&self, element: &Self::Element) -> Descendants<'_, Self>
+    where
+        Self: Sized,
+    {
+        Descendants::new(Walker::Borrowed(self), element)
+    }
+
+    /// Iterates over the descendants of `element` level by level.
+    fn descendants_breadth_first(&self, element: &Self::Element) -> BreadthFirst<'_, Self>
+    where
+        Self: Sized,
+    {
+        BreadthFirst::new(Walker::Borrowed(self), element)
+    }
+
+    /// Iterates over the ancestors of `element`, from its parent up to the
+    /// root.
+    fn ancestors(&self, element: &Self::Element) -> Ancestors<'_, Self>
+    where
+        Self: Sized,
+    {
+        Ancestors::new(Walker::Borrowed(self), element)
+    }
+
+    /// Iterates over the siblings after `element`, in order.
+    fn following_siblings(&self, element: &Self::Element) -> FollowingSiblings<'_, Self>
+    where
+        Self: Sized,
+    {
+        FollowingSiblings::new(Walker::Borrowed(self), element)
+    }
+}
@@ -0,0 +1,638 @@
+use {
+    crate::{
+        backend::{Automation, Element},
+        matcher::{parse_operator, MatchKind, Matcher, OPERATOR_CHARS},
+        property_value::value_text,
+        ControlType, Property, PropertyValue, UiacError, UiacResult,
+    },
+    std::{fmt, ops, str::FromStr},
+};
+
+/// How a property condition compares string values.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PropertyConditionFlags {
+    pub ignore_case: bool,
+    /// Match values that contain the given string. UIA only supports this on
+    /// Windows 10 1809 and later.
+    pub match_substring: bool,
+}
+
+/// A condition described independently of any backend, which can be built
+/// into a backend's own condition type with `build`.
+///
+/// Filters can also be parsed from expressions such as
+/// `ControlType=Button and (Name~="ok" or not IsEnabled=true)`. A match is
+/// `Property=value`, `Property!=value`, `Property~=value` to ignore case or
+/// `Property*=value` to match a substring. Matches combine with `and`, `or`,
+/// `not` (or `&&`, `||` and `!`) and parentheses, and `true` and `false`
+/// match everything and nothing.
+///
+/// UIA can't evaluate the remaining matches, which uiac checks itself:
+/// `Property^=value` and `Property$=value` match a prefix or suffix,
+/// `Property?=glob` a glob such as `"* - Word"` and `Property/=regex` a
+/// regular expression. Besides `!=`, operators can take a `~` before their
+/// `=` to ignore case and then a `_` to normalize whitespace, as in
+/// `Name$~_=" - word"`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Filter {
+    #[default]
+    True,
+    False,
+    Property {
+        property: Property,
+        value: PropertyValue,
+        flags: PropertyConditionFlags,
+    },
+    /// A match that uiac checks against the property's text, as UIA can't.
+    Match {
+        property: Property,
+        matcher: Matcher,
+    },
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+}
+
+impl Filter {
+    pub fn property(property: Property, value: PropertyValue) -> Self {
+        Self::property_ex(property, value, PropertyConditionFlags::default())
+    }
+
+    pub fn property_ex(
+        property: Property,
+        value: PropertyValue,
+        flags: PropertyConditionFlags,
+    ) -> Self {
+        Filter::Property {
+            property,
+            value,
+            flags,
+        }
+    }
+
+    pub fn matching(property: Property, matcher: Matcher) -> Self {
+        Filter::Match { property, matcher }
+    }
+
+    pub fn and(self, other: Filter) -> Self {
+        Filter::And(Box::new(self), Box::new(other))
+    }
+
+    pub fn or(self, other: Filter) -> Self {
+        Filter::Or(Box::new(self), Box::new(other))
+    }
+
+    /// Matches elements that match all of the filters, or every element if
+    /// there are none.
+    pub fn all(filters: impl IntoIterator<Item = Filter>) -> Self {
+        filters
+            .into_iter()
+            .reduce(Filter::and)
+            .unwrap_or(Filter::True)
+    }
+
+    /// Matches elements that match any of the filters, or no element if there
+    /// are none.
+    pub fn any(filters: impl IntoIterator<Item = Filter>) -> Self {
+        filters
+            .into_iter()
+            .reduce(Filter::or)
+            .unwrap_or(Filter::False)
+    }
+
+    /// Builds the filter into a backend condition. `Match`es can't be built,
+    /// so the condition lets through every element they might match, and
+    /// unless the filter `is_native`, what it lets through should be checked
+    /// with `matches`.
+    pub fn build<A: Automation>(&self, automation: &A) -> UiacResult<A::Condition> {
+        self.build_superset(automation, true)
+    }
+
+    /// `positive` is false under an odd number of `Not`s, where a `Match` has
+    /// to build into a condition that matches nothing for the whole to let
+    /// through everything it might match.
+    fn build_superset<A: Automation>(
+        &self,
+        automation: &A,
+        positive: bool,
+    ) -> UiacResult<A::Condition> {
+        match self {
+            Filter::True => automation.create_true_condition(),
+            Filter::False => automation.create_false_condition(),
+            Filter::Property {
+                property,
+                value,
+                flags,
+            } => automation.create_property_condition_ex(*property, value, *flags),
+            Filter::Match { .. } if positive => automation.create_true_condition(),
+            Filter::Match { .. } => automation.create_false_condition(),
+            Filter::And(filter1, filter2) => automation.create_and_condition(
+                &filter1.build_superset(automation, positive)?,
+                &filter2.build_superset(automation, positive)?,
+            ),
+            Filter::Or(filter1, filter2) => automation.create_or_condition(
+                &filter1.build_superset(automation, positive)?,
+                &filter2.build_superset(automation, positive)?,
+            ),
+            Filter::Not(filter) => {
+                automation.create_not_condition(&filter.build_superset(automation, !positive)?)
+            }
+        }
+    }
+
+    /// Whether UIA can evaluate the whole filter, i.e. it has no `Match`es.
+    pub fn is_native(&self) -> bool {
+        match self {
+            Filter::True | Filter::False | Filter::Property { .. } => true,
+            Filter::Match { .. } => false,
+            Filter::And(filter1, filter2) | Filter::Or(filter1, filter2) => {
+                filter1.is_native() && filter2.is_native()
+            }
+            Filter::Not(filter) => filter.is_native(),
+        }
+    }
+
+    /// Checks the filter against an element in uiac rather than in UIA.
+    pub fn matches(&self, element: &impl Element) -> UiacResult<bool> {
+        self.evaluate(&mut |property| element.get_current_property_value(property))
+    }
+
+    /// Checks the filter against the property values `get_value` gives.
+    pub(crate) fn evaluate(
+        &self,
+        get_value: &mut impl FnMut(Property) -> UiacResult<PropertyValue>,
+    ) -> UiacResult<bool> {
+        Ok(match self {
+            Filter::True => true,
+            Filter::False => false,
+            Filter::Property {
+                property,
+                value,
+                flags,
+            } => property_value_matches(&get_value(*property)?, value, *flags),
+            Filter::Match { property, matcher } => value_text(*property, &get_value(*property)?)
+                .is_some_and(|text| matcher.is_match(&text)),
+            Filter::And(filter1, filter2) => {
+                filter1.evaluate(get_value)? && filter2.evaluate(get_value)?
+            }
+            Filter::Or(filter1, filter2) => {
+                filter1.evaluate(get_value)? || filter2.evaluate(get_value)?
+            }
+            Filter::Not(filter) => !filter.evaluate(get_value)?,
+        })
+    }
+}
+
+/// Compares property values the way UIA does, where the flags only apply to
+/// strings.
+fn property_value_matches(
+    actual: &PropertyValue,
+    expected: &PropertyValue,
+    flags: PropertyConditionFlags,
+) -> bool {
+    match (actual, expected) {
+        (PropertyValue::String(actual), PropertyValue::String(expected)) => {
+            let (actual, expected) = if flags.ignore_case {
+                (actual.to_lowercase(), expected.to_lowercase())
+            } else {
+                (actual.clone(), expected.clone())
+            };
+            if flags.match_substring {
+                actual.contains(&expected)
+            } else {
+                actual == expected
+            }
+        }
+        _ => actual == expected,
+    }
+}
+
+impl ops::Not for Filter {
+    type Output = Filter;
+
+    fn not(self) -> Filter {
+        Filter::Not(Box::new(self))
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Filter::True => write!(f, "true"),
+            Filter::False => write!(f, "false"),
+            Filter::Property {
+                property,
+                value,
+                flags,
+            } => {
+                let operator = match (flags.ignore_case, flags.match_substring) {
+                    (false, false) => "=",
+                    (true, false) => "~=",
+                    (false, true) => "*=",
+                    (true, true) => "*~=",
+                };
+                match (property, value) {
+                    (Property::ControlType, PropertyValue::Int(id)) => {
+                        write!(f, "{property}{operator}{}", ControlType::new(*id as u32))
+                    }
+                    (_, PropertyValue::String(s)) => {
+                        write!(f, "{property}{operator}{}", Quoted(s))
+                    }
+                    _ => write!(f, "{property}{operator}{value}"),
+                }
+            }
+            Filter::Match { property, matcher } => write!(
+                f,
+                "{property}{}{}",
+                matcher.operator(),
+                Quoted(matcher.pattern())
+            ),
+            Filter::And(filter1, filter2) => write!(f, "({filter1} and {filter2})"),
+            Filter::Or(filter1, filter2) => write!(f, "({filter1} or {filter2})"),
+            Filter::Not(filter) => write!(f, "not {filter}"),
+        }
+    }
+}
+
+/// A value in double quotes, escaped only as far as the parsers of filters and
+/// selectors unescape, so that values with control characters and the like
+/// read back as they were.
+pub(crate) struct Quoted<'a>(pub(crate) &'a str);
+
+impl fmt::Display for Quoted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"")?;
+        for c in self.0.chars() {
+            if let '"' | '\\' = c {
+                write!(f, "\\")?;
+            }
+            write!(f, "{c}")?;
+        }
+        write!(f, "\"")
+    }
+}
+
+impl FromStr for Filter {
+    type Err = UiacError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { s, pos: 0 };
+        let filter = parser.parse_or()?;
+        parser.skip_whitespace();
+        if parser.pos < s.len() {
+            return Err(parser.error("expected the end of the condition"));
+        }
+        Ok(filter)
+    }
+}
+
+/// A recursive descent parser for filter expressions.
+struct Parser<'a> {
+    s: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.s[self.pos..]
+    }
+
+    fn error(&self, expected: &str) -> UiacError {
+        UiacError::Message(format!(
+            "invalid condition {:?}: {expected} at {:?}",
+            self.s,
+            self.rest()
+        ))
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.pos = self.s.len() - self.rest().trim_start().len();
+    }
+
+    fn eat(&mut self, symbol: &str) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(symbol) {
+            self.pos += symbol.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consumes `keyword` if it appears as a whole word, ignoring case.
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let is_keyword = rest
+            .get(..keyword.len())
+            .is_some_and(|word| word.eq_ignore_ascii_case(keyword))
+            && !rest[keyword.len()..].starts_with(is_word_char);
+        if is_keyword {
+            self.pos += keyword.len();
+        }
+        is_keyword
+    }
+
+    fn parse_or(&mut self) -> UiacResult<Filter> {
+        let mut filter = self.parse_and()?;
+        while self.eat("||") || self.eat_keyword("or") {
+            filter = filter.or(self.parse_and()?);
+        }
+        Ok(filter)
+    }
+
+    fn parse_and(&mut self) -> UiacResult<Filter> {
+        let mut filter = self.parse_unary()?;
+        while self.eat("&&") || self.eat_keyword("and") {
+            filter = filter.and(self.parse_unary()?);
+        }
+        Ok(filter)
+    }
+
+    fn parse_unary(&mut self) -> UiacResult<Filter> {
+        if self.eat("!") || self.eat_keyword("not") {
+            Ok(!self.parse_unary()?)
+        } else if self.eat("(") {
+            let filter = self.parse_or()?;
+            if !self.eat(")") {
+                return Err(self.error("expected \")\""));
+            }
+            Ok(filter)
+        } else if self.eat_keyword("true") {
+            Ok(Filter::True)
+        } else if self.eat_keyword("false") {
+            Ok(Filter::False)
+        } else {
+            self.parse_match()
+        }
+    }
+
+    fn parse_match(&mut self) -> UiacResult<Filter> {
+        self.skip_whitespace();
+        let name_len = self
+            .rest()
+            // Property names never contain "_", which is an operator modifier.
+            .find(|c: char| !c.is_alphanumeric() && c != '.')
+            .unwrap_or(self.rest().len());
+        if name_len == 0 {
+            return Err(self.error("expected a property"));
+        }
+        let property: Property = self.rest()[..name_len].parse()?;
+        self.pos += name_len;
+
+        if self.eat("!=") {
+            let value = property.parse_value(&self.parse_value()?)?;
+            return Ok(!Filter::property(property, value));
+        }
+
+        // An operator is an optional kind, then optional "~" and "_" modifiers,
+        // then "=".
+        self.skip_whitespace();
+        let operator_len = self
+            .rest()
+            .find('=')
+            .filter(|len| {
+                self.rest()[..*len]
+                    .chars()
+                    .all(|c| OPERATOR_CHARS.contains(c))
+            })
+            .ok_or_else(|| self.error("expected an operator such as \"=\", \"!=\" or \"*=\""))?;
+        let operator = &self.rest()[..operator_len];
+        let (kind, flags) = parse_operator(operator)
+            .ok_or_else(|| self.error(&format!("invalid operator \"{operator}=\"")))?;
+        self.pos += operator_len + 1;
+
+        let value = self.parse_value()?;
+        match (kind, flags.normalize_whitespace) {
+            // UIA can compare these itself.
+            (MatchKind::Equals | MatchKind::Contains, false) => Ok(Filter::property_ex(
+                property,
+                property.parse_value(&value)?,
+                PropertyConditionFlags {
+                    ignore_case: flags.ignore_case,
+                    match_substring: kind == MatchKind::Contains,
+                },
+            )),
+            _ => Ok(Filter::matching(
+                property,
+                Matcher::new(kind, value, flags)?,
+            )),
+        }
+    }
+
+    /// Parses either a double-quoted string, in which `\"` and `\\` are
+    /// escapes and other backslashes are kept so regexes read naturally, or a
+    /// bare word that runs until whitespace or a parenthesis. A bare word can
+    /// end in a number in parentheses, as control types like `Unknown(50100)`
+    /// do.
+    fn parse_value(&mut self) -> UiacResult<String> {
+        if self.eat("\"") {
+            let mut value = String::new();
+            let mut chars = self.rest().char_indices();
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '"' => {
+                        self.pos += i + 1;
+                        return Ok(value);
+                    }
+                    '\\' => match chars.next() {
+                        Some((_, c @ ('"' | '\\'))) => value.push(c),
+                        Some((_, c)) => {
+                            value.push('\\');
+                            value.push(c);
+                        }
+                        None => break,
+                    },
+                    c => value.push(c),
+                }
+            }
+            Err(self.error("expected a closing quote"))
+        } else {
+            self.skip_whitespace();
+            let rest = self.rest();
+            let mut len = rest
+                .find(|c: char| c.is_whitespace() || c == '(' || c == ')')
+                .unwrap_or(rest.len());
+            if len == 0 {
+                return Err(self.error("expected a value"));
+            }
+            if let Some(number) = rest[len..].strip_prefix('(') {
+                let digits = number
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(number.len());
+                if digits > 0 && number[digits..].starts_with(')') {
+                    len += digits + 2;
+                }
+            }
+            let value = self.rest()[..len].to_owned();
+            self.pos += len;
+            Ok(value)
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::matcher::MatchFlags};
+
+    #[test]
+    fn filter_from_str_test() -> UiacResult<()> {
+        let name = |s: &str| PropertyValue::String(s.to_owned());
+        let ignore_case = PropertyConditionFlags {
+            ignore_case: true,
+            ..PropertyConditionFlags::default()
+        };
+
+        let filters = [
+            ("true", Filter::True),
+            (" FALSE ", Filter::False),
+            (
+                "ControlType=Button",
+                Filter::property(Property::ControlType, PropertyValue::Int(50000)),
+            ),
+            (
+                r#"Name~="Show \"desktop\"""#,
+                Filter::property_ex(Property::Name, name(r#"Show "desktop""#), ignore_case),
+            ),
+            (
+                "IsEnabled!=true",
+                !Filter::property(Property::IsEnabled, PropertyValue::Bool(true)),
+            ),
+            (
+                "Name=a or Name=b and not Name*=c",
+                Filter::property(Property::Name, name("a")).or(Filter::property(
+                    Property::Name,
+                    name("b"),
+                )
+                .and(!Filter::property_ex(
+                    Property::Name,
+                    name("c"),
+                    PropertyConditionFlags {
+                        match_substring: true,
+                        ..PropertyConditionFlags::default()
+                    },
+                ))),
+            ),
+            (
+                "(Name=a || Name=b) && !(Value.Value=c)",
+                Filter::property(Property::Name, name("a"))
+                    .or(Filter::property(Property::Name, name("b")))
+                    .and(!Filter::property(Property::ValueValue, name("c"))),
+            ),
+            (
+                r#"Name/="^Document\d+ - Word$""#,
+                Filter::matching(
+                    Property::Name,
+                    Matcher::new(
+                        MatchKind::Regex,
+                        r"^Document\d+ - Word$",
+                        MatchFlags::default(),
+                    )?,
+                ),
+            ),
+            (
+                "ClassName?~=*wnd and Name^=Doc",
+                Filter::matching(
+                    Property::ClassName,
+                    Matcher::new(
+                        MatchKind::Glob,
+                        "*wnd",
+                        MatchFlags {
+                            ignore_case: true,
+                            ..MatchFlags::default()
+                        },
+                    )?,
+                )
+                .and(Filter::matching(
+                    Property::Name,
+                    Matcher::new(MatchKind::StartsWith, "Doc", MatchFlags::default())?,
+                )),
+            ),
+            (
+                r#"Name$~_=" - word""#,
+                Filter::matching(
+                    Property::Name,
+                    Matcher::new(
+                        MatchKind::EndsWith,
+                        " - word",
+                        MatchFlags {
+                            ignore_case: true,
+                            normalize_whitespace: true,
+                        },
+                    )?,
+                ),
+            ),
+            (
+                "Name_=a",
+                Filter::matching(
+                    Property::Name,
+                    Matcher::new(
+                        MatchKind::Equals,
+                        "a",
+                        MatchFlags {
+                            normalize_whitespace: true,
+                            ..MatchFlags::default()
+                        },
+                    )?,
+                ),
+            ),
+        ];
+
+        for (s, filter) in filters {
+            assert_eq!(s.parse::<Filter>()?, filter);
+            assert_eq!(filter.to_string().parse::<Filter>()?, filter);
+        }
+
+        // Strings are written as they are, save for quotes and backslashes,
+        // and read back the same.
+        let value = "Say \"hi\"\tto C:\\\n\u{200e}them";
+        let filter = Filter::property(Property::Name, name(value)).or(Filter::matching(
+            Property::AutomationId,
+            Matcher::new(MatchKind::StartsWith, value, MatchFlags::default())?,
+        ));
+        let written = filter.to_string();
+        assert_eq!(
+            written,
+            "(Name=\"Say \\\"hi\\\"\tto C:\\\\\n\u{200e}them\" or \
+             AutomationId^=\"Say \\\"hi\\\"\tto C:\\\\\n\u{200e}them\")"
+        );
+        assert_eq!(written.parse::<Filter>()?, filter);
+
+        // So are control types without a name, which parse with or without
+        // quotes and inside parentheses.
+        let unknown = Filter::property(Property::ControlType, PropertyValue::Int(50100));
+        assert_eq!(unknown.to_string(), "ControlType=Unknown(50100)");
+        assert_eq!(unknown.to_string().parse::<Filter>()?, unknown);
+        assert_eq!(
+            r#"ControlType="Unknown(50100)""#.parse::<Filter>()?,
+            unknown
+        );
+        let filter = (!unknown.clone()).and(unknown.or(Filter::True));
+        assert_eq!(
+            filter.to_string(),
+            "(not ControlType=Unknown(50100) and (ControlType=Unknown(50100) or true))"
+        );
+        assert_eq!(filter.to_string().parse::<Filter>()?, filter);
+
+        for s in [
+            "",
+            "Name",
+            "Name=",
+            "(Name=a",
+            "Name=a Name=b",
+            "Bogus=1",
+            "Name=\"a",
+            "Name_~=a",
+            "Name^*=a",
+            "Name/=(",
+        ] {
+            assert!(s.parse::<Filter>().is_err(), "{s:?} should not parse");
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,227 @@
+use {
+    crate::UiacError,
+    serde::{de, Deserialize, Deserializer, Serialize, Serializer},
+    std::{fmt, str::FromStr},
+};
+
+/// Defines `ControlType` from a single table of variants and their
+/// `UIA_CONTROLTYPE_ID` values.
+macro_rules! control_types {
+    ($($variant:ident = $id:literal,)*) => {
+        #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+        pub enum ControlType {
+            $($variant,)*
+            /// A control type ID that uiac doesn't know about, such as one from
+            /// a newer SDK.
+            Unknown(u32),
+        }
+
+        impl ControlType {
+            /// Every known control type, excluding `Unknown`.
+            pub const ALL: &'static [ControlType] = &[$(ControlType::$variant,)*];
+
+            /// Maps a `UIA_CONTROLTYPE_ID` value to its control type.
+            pub fn new(id: u32) -> Self {
+                match id {
+                    $($id => ControlType::$variant,)*
+                    _ => ControlType::Unknown(id),
+                }
+            }
+
+            /// The control type's `UIA_CONTROLTYPE_ID` value.
+            pub fn id(&self) -> u32 {
+                match self {
+                    $(ControlType::$variant => $id,)*
+                    ControlType::Unknown(id) => *id,
+                }
+            }
+
+            fn name(&self) -> Option<&'static str> {
+                match self {
+                    $(ControlType::$variant => Some(stringify!($variant)),)*
+                    ControlType::Unknown(_) => None,
+                }
+            }
+        }
+    };
+}
+
+control_types! {
+    AppBar = 50040,
+    Button = 50000,
+    Calendar = 50001,
+    CheckBox = 50002,
+    ComboBox = 50003,
+    Custom = 50025,
+    DataGrid = 50028,
+    DataItem = 50029,
+    Document = 50030,
+    Edit = 50004,
+    Group = 50026,
+    Header = 50034,
+    HeaderItem = 50035,
+    Hyperlink = 50005,
+    Image = 50006,
+    List = 50008,
+    ListItem = 50007,
+    MenuBar = 50010,
+    Menu = 50009,
+    MenuItem = 50011,
+    Pane = 50033,
+    ProgressBar = 50012,
+    RadioButton = 50013,
+    ScrollBar = 50014,
+    SemanticZoom = 50039,
+    Separator = 50038,
+    Slider = 50015,
+    Spinner = 50016,
+    SplitButton = 50031,
+    StatusBar = 50017,
+    Tab = 50018,
+    TabItem = 50019,
+    Table = 50036,
+    Text = 50020,
+    Thumb = 50027,
+    TitleBar = 50037,
+    ToolBar = 50021,
+    ToolTip = 50022,
+    Tree = 50023,
+    TreeItem = 50024,
+    Window = 50032,
+}
+
+impl fmt::Display for ControlType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "{name}"),
+            None => write!(f, "Unknown({})", self.id()),
+        }
+    }
+}
+
+impl FromStr for ControlType {
+    type Err = UiacError;
+
+    /// Parses a control type name, ignoring case. `Unknown(id)` is accepted for
+    /// any ID, and maps to the named control type if the ID is a known one.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(control_type) = ControlType::ALL
+            .iter()
+            .find(|control_type| control_type.name().unwrap().eq_ignore_ascii_case(s))
+        {
+            return Ok(*control_type);
+        }
+
+        s.strip_prefix("Unknown(")
+            .and_then(|s| s.strip_suffix(')'))
+            .and_then(|id| id.parse().ok())
+            .map(ControlType::new)
+            .ok_or_else(|| UiacError::InvalidControlType(s.to_owned()))
+    }
+}
+
+impl Serialize for ControlType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ControlType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn control_type_display_test() {
+        let mappings = [
+            (ControlType::AppBar, "AppBar"),
+            (ControlType::Button, "Button"),
+            (ControlType::Calendar, "Calendar"),
+            (ControlType::CheckBox, "CheckBox"),
+            (ControlType::ComboBox, "ComboBox"),
+            (ControlType::Custom, "Custom"),
+            (ControlType::DataGrid, "DataGrid"),
+            (ControlType::DataItem, "DataItem"),
+            (ControlType::Document, "Document"),
+            (ControlType::Edit, "Edit"),
+            (ControlType::Group, "Group"),
+            (ControlType::Header, "Header"),
+            (ControlType::HeaderItem, "HeaderItem"),
+            (ControlType::Hyperlink, "Hyperlink"),
+            (ControlType::Image, "Image"),
+            (ControlType::List, "List"),
+            (ControlType::ListItem, "ListItem"),
+            (ControlType::MenuBar, "MenuBar"),
+            (ControlType::Menu, "Menu"),
+            (ControlType::MenuItem, "MenuItem"),
+            (ControlType::Pane, "Pane"),
+            (ControlType::ProgressBar, "ProgressBar"),
+            (ControlType::RadioButton, "RadioButton"),
+            (ControlType::ScrollBar, "ScrollBar"),
+            (ControlType::SemanticZoom, "SemanticZoom"),
+            (ControlType::Separator, "Separator"),
+            (ControlType::Slider, "Slider"),
+            (ControlType::Spinner, "Spinner"),
+            (ControlType::SplitButton, "SplitButton"),
+            (ControlType::StatusBar, "StatusBar"),
+            (ControlType::Tab, "Tab"),
+            (ControlType::TabItem, "TabItem"),
+            (ControlType::Table, "Table"),
+            (ControlType::Text, "Text"),
+            (ControlType::Thumb, "Thumb"),
+            (ControlType::TitleBar, "TitleBar"),
+            (ControlType::ToolBar, "ToolBar"),
+            (ControlType::ToolTip, "ToolTip"),
+            (ControlType::Tree, "Tree"),
+            (ControlType::TreeItem, "TreeItem"),
+            (ControlType::Window, "Window"),
+            (ControlType::Unknown(50100), "Unknown(50100)"),
+        ];
+
+        for (control_type, string) in mappings {
+            assert_eq!(control_type.to_string(), string);
+        }
+    }
+
+    #[test]
+    fn control_type_new_test() {
+        for control_type in ControlType::ALL {
+            assert_eq!(ControlType::new(control_type.id()), *control_type);
+        }
+
+        assert_eq!(ControlType::new(50100), ControlType::Unknown(50100));
+        assert_eq!(ControlType::Unknown(50100).id(), 50100);
+    }
+
+    #[test]
+    fn control_type_from_str_test() -> Result<(), UiacError> {
+        for control_type in ControlType::ALL {
+            assert_eq!(
+                control_type.to_string().parse::<ControlType>()?,
+                *control_type
+            );
+        }
+
+        assert_eq!(
+            "Unknown(50100)".parse::<ControlType>()?,
+            ControlType::Unknown(50100)
+        );
+        assert_eq!(
+            "Unknown(50000)".parse::<ControlType>()?,
+            ControlType::Button
+        );
+        assert_eq!("listitem".parse::<ControlType>()?, ControlType::ListItem);
+
+        assert!("Buton".parse::<ControlType>().is_err());
+        assert!("Unknown(abc)".parse::<ControlType>().is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+use {
+    crate::{
+        backend::{Element, TreeWalker},
+        traversal::{Event, Order, Traversal, Walk},
+        ControlType, IndexPath, UiacResult,
+    },
+    std::io::Write,
+};
+
+/// How to lay out a DOT graph of the tree.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct DotOptions {
+    /// Group each child of the starting element and its subtree into a
+    /// cluster, for when the walk starts at the desktop and those are the
+    /// top-level windows.
+    pub clusters: bool,
+    /// Fill each node with a colour for its control type.
+    pub color_by_control_type: bool,
+}
+
+/// Writes `element` and its subtree as a Graphviz DOT graph, with each node
+/// labelled by its control type and name. If `path` is given, nodes are also
+/// labelled with their index paths.
+pub(crate) fn write_dot<W: TreeWalker>(
+    walk: &Walk<W>,
+    element: &W::Element,
+    path: Option<IndexPath>,
+    options: DotOptions,
+    out: &mut impl Write,
+) -> UiacResult<()> {
+    writeln!(out, "digraph uiac {{")?;
+    writeln!(out, "  rankdir=LR;")?;
+    writeln!(out, r#"  node [shape=box, fontname="Segoe UI"];"#)?;
+
+    // The ids of the nodes entered but not left, so children can be joined to
+    // their parents. Edges are written after all the nodes, outside the
+    // clusters, so that an edge into a cluster doesn't pull its parent in.
+    let mut open: Vec<usize> = vec![];
+    let mut edges = vec![];
+    let mut next_id = 0;
+    let mut cluster_count = 0;
+    for event in Traversal::new(walk, element, path, Order::DepthFirst) {
+        match event? {
+            Event::Enter(visit) => {
+                let id = next_id;
+                next_id += 1;
+                if let Some(parent) = open.last() {
+                    edges.push((*parent, id));
+                }
+
+                let name = visit.element.get_current_name()?;
+                let control_type = visit.element.get_current_control_type()?;
+                if options.clusters && visit.depth == 1 {
+                    writeln!(out, "  subgraph cluster_{cluster_count} {{")?;
+                    writeln!(
+                        out,
+                        r#"    label="{}";"#,
+                        escape(&label(control_type, &name, None))
+                    )?;
+                    cluster_count += 1;
+                }
+
+                let indent = if options.clusters && visit.depth >= 1 {
+                    "    "
+                } else {
+                    "  "
+                };
+                write!(
+                    out,
+                    r#"{indent}n{id} [label="{}""#,
+                    escape(&label(control_type, &name, visit.path.as_ref()))
+                )?;
+                if options.color_by_control_type {
+                    write!(
+                        out,
+                        r#", style=filled, fillcolor="{}""#,
+                        color(control_type)
+                    )?;
+                }
+                writeln!(out, "];")?;
+
+                open.push(id);
+            }
+            Event::Leave => {
+                open.pop();
+                if options.clusters && open.len() == 1 {
+                    writeln!(out, "  }}")?;
+                }
+            }
+        }
+    }
+
+    for (parent, child) in edges {
+        writeln!(out, "  n{parent} -> n{child};")?;
+    }
+    writeln!(out, "}}")?;
+
+    Ok(())
+}
+
+/// A node's label: its control type, then its name if it has one, then its
+/// index path if given, each on a line of its own.
+fn label(control_type: ControlType, name: &str, path: Option<&IndexPath>) -> String {
+    let mut label = control_type.to_string();
+    if !name.is_empty() {
+        label.push_str(&format!("\n\"{name}\""));
+    }
+    if let Some(path) = path {
+        label.push_str(&format!("\n{path}"));
+    }
+    label
+}
+
+/// A light fill colour for a control type, as DOT's "hue saturation value".
+/// Stepping the hue by the golden ratio keeps neighbouring control types apart.
+fn color(control_type: ControlType) -> String {
+    let hue = (f64::from(control_type.id()) * 0.618_033_988_75).fract();
+    format!("{hue:.3} 0.350 0.950")
+}
+
+/// Escapes text for a quoted DOT string.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_test() {
+        assert_eq!(escape("plain"), "plain");
+        assert_eq!(escape(r#"say "hi""#), r#"say \"hi\""#);
+        assert_eq!(escape(r"C:\Windows"), r"C:\\Windows");
+        assert_eq!(escape("line\r\nbreak"), r"line\nbreak");
+    }
+}
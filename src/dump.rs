@@ -1,48 +1,205 @@
 use {
     crate::{
-        wrappers::{Automation, Element, TreeWalker},
-        UiacResult,
+        backend::{Automation, Element, TreeWalker},
+        dot::{write_dot, DotOptions},
+        ndjson::write_ndjson,
+        page_source::write_page_source,
+        report::write_report,
+        snapshot::{write_json, Snapshot},
+        table::{write_table, Delimiter},
+        traversal::{Event, Order, Traversal, Walk},
+        ControlType, Filter, IndexPath, Property, PropertyValue, Root, UiacResult, View,
     },
-    windows::Win32::UI::Accessibility::{UIA_ControlTypePropertyId, UIA_NamePropertyId},
+    std::io::Write,
 };
 
-pub fn dump() -> UiacResult<()> {
-    let automation = Automation::new()?;
-    let walker = automation.create_tree_walker(&automation.create_true_condition()?)?;
-    let root = automation.get_root_element()?;
-
-    dump_recursive(&walker, &root, 0)
+#[derive(Clone, Copy, Debug, Default, clap::ValueEnum)]
+pub enum Format {
+    /// An indented tree, one element per line.
+    #[default]
+    Text,
+    /// A JSON snapshot of the whole tree.
+    Json,
+    /// XML in the layout of WinAppDriver's page source.
+    Xml,
+    /// A Graphviz DOT graph.
+    Dot,
+    /// A self-contained HTML page for browsing the tree.
+    Html,
+    /// Comma-separated values, one row per element.
+    Csv,
+    /// Tab-separated values, one row per element.
+    Tsv,
+    /// Newline-delimited JSON, one object per element, written as the tree is
+    /// walked.
+    Ndjson,
 }
 
-fn dump_recursive(walker: &TreeWalker, element: &Element, indent_level: usize) -> UiacResult<()> {
-    print_element(element, indent_level)?;
+#[derive(Debug)]
+pub struct DumpOptions {
+    pub format: Format,
+    /// The properties to print for each element, in order.
+    pub properties: Vec<Property>,
+    pub root: Root,
+    /// How many levels below the root to descend, or `None` for no limit.
+    pub max_depth: Option<usize>,
+    pub view: View,
+    /// Only elements matching this are printed. Their descendants are still
+    /// searched.
+    pub condition: Filter,
+    /// Print each element's index path, or record it in JSON. Tables and
+    /// NDJSON always have paths.
+    pub paths: bool,
+    /// The order to print elements in as text, table rows or NDJSON. JSON
+    /// always nests them.
+    pub order: Order,
+    /// Fill the nodes of a DOT graph with a colour for their control type.
+    pub color_by_control_type: bool,
+}
 
-    let mut child_option = walker.get_first_child_element(element)?;
-    while let Some(child) = child_option {
-        dump_recursive(walker, &child, indent_level + 1)?;
-        child_option = walker.get_next_sibling_element(&child)?;
+impl Default for DumpOptions {
+    fn default() -> Self {
+        Self {
+            format: Format::default(),
+            properties: vec![Property::Name, Property::ControlType],
+            root: Root::default(),
+            max_depth: None,
+            view: View::default(),
+            condition: Filter::True,
+            paths: false,
+            order: Order::default(),
+            color_by_control_type: false,
+        }
     }
-
-    Ok(())
 }
 
-fn print_element(element: &Element, indent_level: usize) -> UiacResult<()> {
-    print!("{}│ ", "  ".repeat(indent_level));
+pub fn dump<A: Automation>(
+    automation: &A,
+    options: &DumpOptions,
+    out: &mut impl Write,
+) -> UiacResult<()> {
+    let raw_walker = automation.create_tree_walker(&automation.get_raw_view_condition()?)?;
+    let root = options.root.find(automation, &raw_walker)?;
 
-    let name = element
-        .get_current_property_value(UIA_NamePropertyId)?
-        .as_string()?;
-    let name = if name.is_empty() {
-        "[no name]".to_owned()
+    let paths =
+        options.paths || matches!(options.format, Format::Csv | Format::Tsv | Format::Ndjson);
+    let (walker, filter, root_path) = if paths {
+        // Index paths count positions in the raw view, so walk that and leave
+        // out what the view and condition don't match ourselves.
+        (
+            raw_walker,
+            options.view.filter().and(options.condition.clone()),
+            Some(IndexPath::of(automation, &root)?),
+        )
     } else {
-        format!("{name:?}")
+        // The walker lets through every element the condition might match, so
+        // only check what UIA couldn't.
+        let filter = if options.condition.is_native() {
+            Filter::True
+        } else {
+            options.condition.clone()
+        };
+        let walker = automation.create_tree_walker(&automation.create_and_condition(
+            &options.view.condition(automation)?,
+            &options.condition.build(automation)?,
+        )?)?;
+        (walker, filter, None)
+    };
+    let walk = Walk {
+        walker: &walker,
+        properties: &options.properties,
+        max_depth: options.max_depth,
+        filter: &filter,
     };
 
-    let control_type = element
-        .get_current_property_value(UIA_ControlTypePropertyId)?
-        .as_control_type()?;
+    match options.format {
+        Format::Text => print_tree(&walk, &root, root_path, options.order, out),
+        Format::Json => {
+            let snapshot = Snapshot::capture(&walk, &root, root_path, options.root.to_string())?;
+            write_json(&snapshot, true, out)?;
+            writeln!(out)?;
+            Ok(())
+        }
+        Format::Xml => write_page_source(&walk, &root, root_path, true, out),
+        Format::Dot => {
+            let options = DotOptions {
+                // The desktop's children are the top-level windows.
+                clusters: automation.compare_elements(&root, &automation.get_root_element()?)?,
+                color_by_control_type: options.color_by_control_type,
+            };
+            write_dot(&walk, &root, root_path, options, out)
+        }
+        Format::Html => {
+            let snapshot = Snapshot::capture(&walk, &root, root_path, options.root.to_string())?;
+            write_report(&snapshot, out)
+        }
+        Format::Csv => write_table(
+            &walk,
+            &root,
+            root_path,
+            options.order,
+            Delimiter::Comma,
+            out,
+        ),
+        Format::Tsv => write_table(&walk, &root, root_path, options.order, Delimiter::Tab, out),
+        Format::Ndjson => write_ndjson(&walk, &root, root_path, options.order, out),
+    }
+}
+
+/// Prints `element` and its subtree as an indented tree, visiting elements in
+/// `order`. If `path` is given, it's the element's index path, and the walker
+/// must walk the raw view for its descendants' paths to be right.
+pub(crate) fn print_tree<W: TreeWalker>(
+    walk: &Walk<W>,
+    element: &W::Element,
+    path: Option<IndexPath>,
+    order: Order,
+    out: &mut impl Write,
+) -> UiacResult<()> {
+    for event in Traversal::new(walk, element, path, order) {
+        if let Event::Enter(visit) = event? {
+            print_element(
+                &visit.element,
+                visit.path.as_ref(),
+                visit.depth,
+                walk.properties,
+                out,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+fn print_element(
+    element: &impl Element,
+    path: Option<&IndexPath>,
+    indent_level: usize,
+    properties: &[Property],
+    out: &mut impl Write,
+) -> UiacResult<()> {
+    write!(out, "{}│ ", "  ".repeat(indent_level))?;
+    if let Some(path) = path {
+        write!(out, "{path}: ")?;
+    }
 
-    println!("Name = {name}, ControlType = {control_type}");
+    let properties = properties
+        .iter()
+        .map(|property| {
+            let value = element.get_current_property_value(*property)?;
+            Ok(format!("{property} = {}", format_value(*property, &value)))
+        })
+        .collect::<UiacResult<Vec<_>>>()?;
+
+    writeln!(out, "{}", properties.join(", "))?;
 
     Ok(())
 }
+
+pub(crate) fn format_value(property: Property, value: &PropertyValue) -> String {
+    match (property, value) {
+        (Property::Name, PropertyValue::String(name)) if name.is_empty() => "[no name]".to_owned(),
+        (Property::ControlType, PropertyValue::Int(id)) => ControlType::new(*id as u32).to_string(),
+        _ => value.to_string(),
+    }
+}
@@ -0,0 +1,297 @@
+use {
+    crate::{
+        backend::{Automation, Element},
+        dot::{write_dot, DotOptions},
+        dump::print_tree,
+        memory::Node,
+        ndjson::write_ndjson,
+        page_source::write_page_source,
+        report::write_report,
+        selector::Selector,
+        snapshot::{capture_tree, recorded_properties, write_json, Snapshot},
+        table::{write_header, write_rows, Delimiter},
+        traversal::{Order, Walk},
+        xpath::{XPath, XPathValue},
+        Filter, Format, IndexPath, Property, Root, UiacError, UiacResult, View,
+    },
+    std::io::Write,
+};
+
+/// Which elements around the starting element a search covers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum TreeScope {
+    /// The element's direct children.
+    Children,
+    /// Everything below the element.
+    #[default]
+    Descendants,
+    /// The element itself and everything below it.
+    Subtree,
+}
+
+#[derive(Debug)]
+pub struct FindOptions {
+    pub format: Format,
+    /// The properties to print for each match, in order.
+    pub properties: Vec<Property>,
+    /// Where the search starts.
+    pub root: Root,
+    pub scope: TreeScope,
+    /// The view whose elements are candidates, and in which the subtrees of
+    /// matches are printed.
+    pub view: View,
+    pub condition: Filter,
+    /// Find the elements matched by a selector instead of by `scope` and
+    /// `condition`.
+    pub selector: Option<Selector>,
+    /// Evaluate an XPath instead of searching by `scope` and `condition`.
+    /// XPaths that give strings, numbers or booleans print those values.
+    pub xpath: Option<XPath>,
+    /// Stop at the first match, and fail if there isn't one.
+    pub first: bool,
+    /// Print only the number of matches.
+    pub count: bool,
+    /// How many levels below each match to print, or `None` for no limit.
+    pub max_depth: Option<usize>,
+}
+
+impl Default for FindOptions {
+    fn default() -> Self {
+        Self {
+            format: Format::default(),
+            properties: vec![Property::Name, Property::ControlType],
+            root: Root::default(),
+            scope: TreeScope::default(),
+            view: View::default(),
+            condition: Filter::True,
+            selector: None,
+            xpath: None,
+            first: false,
+            count: false,
+            max_depth: Some(0),
+        }
+    }
+}
+
+/// Searches the tree for elements matching `options.condition` and prints
+/// them, each followed by its subtree down to `options.max_depth`.
+pub fn find<A: Automation>(
+    automation: &A,
+    options: &FindOptions,
+    out: &mut impl Write,
+) -> UiacResult<()> {
+    let root = options.root.find(
+        automation,
+        &automation.create_tree_walker(&automation.create_true_condition()?)?,
+    )?;
+
+    let view_condition = options.view.condition(automation)?;
+    let walker = automation.create_tree_walker(&view_condition)?;
+
+    let matches = match (&options.selector, &options.xpath) {
+        (Some(selector), _) if options.first => {
+            selector.find_first(&walker, &root)?.into_iter().collect()
+        }
+        (Some(selector), _) => selector.find_all(&walker, &root)?,
+        (None, Some(xpath)) => match xpath.evaluate(&walker, &root)? {
+            XPathValue::Elements(mut elements) => {
+                if options.first {
+                    elements.truncate(1);
+                }
+                elements
+            }
+            value => return print_xpath_value(xpath, value, options, out),
+        },
+        (None, None) => {
+            let condition = automation
+                .create_and_condition(&view_condition, &options.condition.build(automation)?)?;
+            if options.condition.is_native() {
+                if options.first {
+                    root.find_first(options.scope, &condition)?
+                        .into_iter()
+                        .collect()
+                } else {
+                    root.find_all(options.scope, &condition)?
+                }
+            } else {
+                // UIA found every element the condition might match, so check
+                // the rest here.
+                let mut matches = vec![];
+                for element in root.find_all(options.scope, &condition)? {
+                    if options.condition.matches(&element)? {
+                        matches.push(element);
+                        if options.first {
+                            break;
+                        }
+                    }
+                }
+                matches
+            }
+        }
+    };
+
+    if options.first && matches.is_empty() {
+        return Err(UiacError::Message(
+            match (&options.selector, &options.xpath) {
+                (Some(selector), _) => format!("no element matches {selector}"),
+                (None, Some(xpath)) => format!("no element matches {xpath}"),
+                (None, None) => format!("no element matches {}", options.condition),
+            },
+        ));
+    }
+
+    if options.count {
+        writeln!(out, "{}", matches.len())?;
+        return Ok(());
+    }
+
+    // Tables and reports give each element's index path, as dump's do. Paths
+    // count positions in the raw view, so for them the matches' subtrees are
+    // walked in that, leaving out what the view doesn't match.
+    let paths = matches!(options.format, Format::Html | Format::Csv | Format::Tsv);
+    let (walker, filter) = if paths {
+        let raw_condition = automation.get_raw_view_condition()?;
+        (
+            automation.create_tree_walker(&raw_condition)?,
+            options.view.filter(),
+        )
+    } else {
+        (walker, Filter::True)
+    };
+    let walk = Walk {
+        walker: &walker,
+        properties: &options.properties,
+        max_depth: options.max_depth,
+        filter: &filter,
+    };
+    match options.format {
+        Format::Text => {
+            for element in &matches {
+                print_tree(&walk, element, None, Order::DepthFirst, out)?;
+            }
+        }
+        Format::Json => {
+            let nodes = matches
+                .iter()
+                .map(|element| capture_tree(&walk, element, None))
+                .collect::<UiacResult<Vec<_>>>()?;
+            write_json(&nodes, true, out)?;
+            writeln!(out)?;
+        }
+        // Without paths, each line's depth is below the match it's part of.
+        Format::Ndjson => {
+            for element in &matches {
+                write_ndjson(&walk, element, None, Order::DepthFirst, out)?;
+            }
+        }
+        // Each match is written as an element of its own, without the XML
+        // declaration that would make it a document.
+        Format::Xml => {
+            for element in &matches {
+                write_page_source(&walk, element, None, false, out)?;
+            }
+        }
+        // Likewise, each match gets a graph of its own.
+        Format::Dot => {
+            for element in &matches {
+                write_dot(&walk, element, None, DotOptions::default(), out)?;
+            }
+        }
+        // One header, then the rows of each match and its subtree.
+        Format::Csv | Format::Tsv => {
+            let delimiter = match options.format {
+                Format::Csv => Delimiter::Comma,
+                _ => Delimiter::Tab,
+            };
+            write_header(&walk, delimiter, out)?;
+            for element in &matches {
+                let path = Some(IndexPath::of(automation, element)?);
+                write_rows(&walk, element, path, Order::DepthFirst, delimiter, out)?;
+            }
+        }
+        // A report is of one tree, so the matches hang from the root they
+        // were found under.
+        Format::Html => {
+            let mut tree = Node {
+                name: root.get_current_name()?,
+                control_type: root.get_current_control_type()?,
+                path: Some(IndexPath::of(automation, &root)?),
+                properties: recorded_properties(&walk, &root)?,
+                children: vec![],
+            };
+            for element in &matches {
+                let path = Some(IndexPath::of(automation, element)?);
+                tree.children.push(capture_tree(&walk, element, path)?);
+            }
+            write_report(&Snapshot::new(tree, options.root.to_string())?, out)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints the result of an XPath that doesn't select elements, such as
+/// `//Button/@Name` or `count(//Button)`.
+fn print_xpath_value<E>(
+    xpath: &XPath,
+    value: XPathValue<E>,
+    options: &FindOptions,
+    out: &mut impl Write,
+) -> UiacResult<()> {
+    let value = match value {
+        XPathValue::Attributes(mut values) => {
+            if options.first {
+                if values.is_empty() {
+                    return Err(UiacError::Message(format!("no attribute matches {xpath}")));
+                }
+                values.truncate(1);
+            }
+            if options.count {
+                writeln!(out, "{}", values.len())?;
+                return Ok(());
+            }
+            serde_json::Value::from(values)
+        }
+        _ if options.count => {
+            return Err(UiacError::Message(format!(
+                "can't count the result of {xpath}, as it doesn't select elements or attributes"
+            )))
+        }
+        XPathValue::String(s) => serde_json::Value::from(s),
+        // Counts and positions read better as integers.
+        XPathValue::Number(n) if n.fract() == 0.0 => serde_json::Value::from(n as i64),
+        XPathValue::Number(n) => serde_json::Value::from(n),
+        XPathValue::Bool(b) => serde_json::Value::from(b),
+        XPathValue::Elements(_) => unreachable!("elements are printed as a tree"),
+    };
+
+    match options.format {
+        // Values aren't elements, so there's nothing to draw or nest.
+        Format::Text | Format::Xml | Format::Dot | Format::Html | Format::Csv | Format::Tsv => {
+            match value {
+                serde_json::Value::Array(values) => {
+                    for value in values {
+                        writeln!(out, "{}", value.as_str().unwrap_or_default())?;
+                    }
+                }
+                serde_json::Value::String(s) => writeln!(out, "{s}")?,
+                value => writeln!(out, "{value}")?,
+            }
+        }
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut *out, &value)?;
+            writeln!(out)?;
+        }
+        // One value to a line.
+        Format::Ndjson => match value {
+            serde_json::Value::Array(values) => {
+                for value in values {
+                    writeln!(out, "{value}")?;
+                }
+            }
+            value => writeln!(out, "{value}")?,
+        },
+    }
+
+    Ok(())
+}
@@ -0,0 +1,143 @@
+use {
+    crate::{
+        backend::{Automation, TreeWalker},
+        UiacError, UiacResult,
+    },
+    serde::{de, Deserialize, Deserializer, Serialize, Serializer},
+    std::{fmt, str::FromStr},
+};
+
+/// Where an element sits in the tree, as its ancestors' positions among their
+/// siblings. It's written like `0/3/1/4`, where the leading `0` is the desktop,
+/// `0/3` its fourth child and so on. Positions are counted in the raw view, so
+/// they don't depend on which view a command walks.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IndexPath(pub Vec<usize>);
+
+impl IndexPath {
+    /// The path of the element's child at `index`.
+    pub fn child(&self, index: usize) -> Self {
+        let mut path = self.0.clone();
+        path.push(index);
+        IndexPath(path)
+    }
+
+    /// The path of the element's parent, or `None` for the desktop.
+    pub fn parent(&self) -> Option<Self> {
+        let (_, path) = self.0.split_last()?;
+        Some(IndexPath(path.to_vec()))
+    }
+
+    /// Finds the element at the path below `desktop`, walking with a raw view
+    /// `walker`.
+    pub fn resolve<W: TreeWalker>(
+        &self,
+        walker: &W,
+        desktop: &W::Element,
+    ) -> UiacResult<W::Element> {
+        let mut element = desktop.clone();
+        for index in &self.0 {
+            element = walker
+                .children(&element)
+                .nth(*index)
+                .transpose()?
+                .ok_or_else(|| UiacError::Message(format!("there's no element at {self}")))?;
+        }
+        Ok(element)
+    }
+
+    /// Finds the path of `element` by climbing to the desktop, counting the
+    /// siblings before each element on the way.
+    pub fn of<A: Automation>(automation: &A, element: &A::Element) -> UiacResult<Self> {
+        let walker = automation.create_tree_walker(&automation.get_raw_view_condition()?)?;
+
+        let mut path = vec![];
+        let mut current = element.clone();
+        while let Some(parent) = walker.get_parent_element(&current)? {
+            let mut index = 0;
+            let mut sibling = current;
+            while let Some(previous) = walker.get_previous_sibling_element(&sibling)? {
+                index += 1;
+                sibling = previous;
+            }
+            path.push(index);
+            current = parent;
+        }
+
+        if !automation.compare_elements(&current, &automation.get_root_element()?)? {
+            return Err(UiacError::Message(
+                "the element isn't below the desktop".to_owned(),
+            ));
+        }
+        path.reverse();
+        Ok(IndexPath(path))
+    }
+}
+
+impl fmt::Display for IndexPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0")?;
+        for index in &self.0 {
+            write!(f, "/{index}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for IndexPath {
+    type Err = UiacError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || UiacError::Message(format!("invalid index path {s:?}"));
+
+        let mut indexes = s.trim().split('/');
+        if indexes.next() != Some("0") {
+            return Err(UiacError::Message(format!(
+                "invalid index path {s:?}: paths start with 0, the desktop"
+            )));
+        }
+
+        Ok(IndexPath(
+            indexes
+                .map(|index| index.parse().map_err(|_| error()))
+                .collect::<UiacResult<_>>()?,
+        ))
+    }
+}
+
+impl Serialize for IndexPath {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for IndexPath {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_path_from_str_test() -> UiacResult<()> {
+        assert_eq!("0".parse::<IndexPath>()?, IndexPath(vec![]));
+        assert_eq!("0/3/1/4".parse::<IndexPath>()?, IndexPath(vec![3, 1, 4]));
+        assert_eq!(IndexPath(vec![3, 1, 4]).to_string(), "0/3/1/4");
+        assert_eq!(
+            IndexPath(vec![3, 1, 4]).parent(),
+            Some(IndexPath(vec![3, 1]))
+        );
+        assert_eq!(IndexPath(vec![]).parent(), None);
+
+        for s in ["", "1/2", "0/", "0/a", "0//1", "0/-1"] {
+            assert!(s.parse::<IndexPath>().is_err(), "{s:?} should not parse");
+        }
+
+        Ok(())
+    }
+}
@@ -1,17 +1,70 @@
-use std::{fmt, string::FromUtf16Error};
-
-#[cfg(not(target_os = "windows"))]
-compile_error!("uiac is only supported on Windows.");
+use std::{fmt, io, string::FromUtf16Error};
 
+pub mod backend;
+mod condition;
+mod control_type;
+mod dot;
 mod dump;
-mod wrappers;
+mod find;
+mod index_path;
+mod locator;
+mod matcher;
+pub mod memory;
+mod navigation;
+mod ndjson;
+mod page_source;
+mod property;
+mod property_value;
+mod report;
+mod resolve;
+mod root;
+pub mod selector;
+pub mod snapshot;
+mod table;
+mod traversal;
+mod view;
+#[cfg(windows)]
+pub mod wrappers;
+pub mod xpath;
 
-pub use dump::dump;
+pub use {
+    condition::{Filter, PropertyConditionFlags},
+    control_type::ControlType,
+    dump::{dump, DumpOptions, Format},
+    find::{find, FindOptions, TreeScope},
+    index_path::IndexPath,
+    locator::locate,
+    matcher::{MatchFlags, MatchKind, Matcher},
+    navigation::{breadcrumb, Ancestors, BreadthFirst, Children, Descendants, FollowingSiblings},
+    property::{Property, PropertyList, PropertyMatch, ValueType},
+    property_value::{ElementRef, PropertyValue},
+    resolve::{resolve, ResolveOptions},
+    root::Root,
+    traversal::Order,
+    view::View,
+};
 
 #[derive(Debug)]
 pub enum UiacError {
     Message(String),
     InvalidVariantType,
+    InvalidControlType(String),
+    InvalidProperty(String),
+    /// A selector that couldn't be parsed, with the byte offset of the problem.
+    InvalidSelector {
+        selector: String,
+        position: usize,
+        message: String,
+    },
+    /// An XPath that couldn't be parsed, with the byte offset of the problem.
+    InvalidXPath {
+        xpath: String,
+        position: usize,
+        message: String,
+    },
+    Io(io::Error),
+    Json(serde_json::Error),
+    #[cfg(windows)]
     Windows(windows::core::Error),
     FromUtf16(FromUtf16Error),
 }
@@ -21,17 +74,51 @@ impl fmt::Display for UiacError {
         match self {
             UiacError::Message(s) => write!(f, "{s}"),
             UiacError::InvalidVariantType => write!(f, "invalid variant type"),
+            UiacError::InvalidControlType(s) => write!(f, "invalid control type {s:?}"),
+            UiacError::InvalidProperty(s) => write!(f, "invalid property {s:?}"),
+            UiacError::InvalidSelector {
+                selector,
+                position,
+                message,
+            } => write_syntax_error(f, "selector", selector, *position, message),
+            UiacError::InvalidXPath {
+                xpath,
+                position,
+                message,
+            } => write_syntax_error(f, "XPath", xpath, *position, message),
+            UiacError::Io(err) => write!(f, "{err}"),
+            UiacError::Json(err) => write!(f, "{err}"),
+            #[cfg(windows)]
             UiacError::Windows(err) => write!(f, "{err}"),
             UiacError::FromUtf16(err) => write!(f, "{err}"),
         }
     }
 }
 
+/// Writes a parse error with a caret under the offending part of `source`.
+fn write_syntax_error(
+    f: &mut fmt::Formatter<'_>,
+    kind: &str,
+    source: &str,
+    position: usize,
+    message: &str,
+) -> fmt::Result {
+    let indent = " ".repeat(source[..position].chars().count());
+    write!(f, "invalid {kind}: {message}\n  {source}\n  {indent}^")
+}
+
 impl std::error::Error for UiacError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             UiacError::Message(_) => None,
             UiacError::InvalidVariantType => None,
+            UiacError::InvalidControlType(_) => None,
+            UiacError::InvalidProperty(_) => None,
+            UiacError::InvalidSelector { .. } => None,
+            UiacError::InvalidXPath { .. } => None,
+            UiacError::Io(err) => Some(err),
+            UiacError::Json(err) => Some(err),
+            #[cfg(windows)]
             UiacError::Windows(err) => Some(err),
             UiacError::FromUtf16(err) => Some(err),
         }
@@ -48,11 +135,15 @@ macro_rules! derive_from {
     };
 }
 
+derive_from!(io::Error, Io);
+derive_from!(serde_json::Error, Json);
+#[cfg(windows)]
 derive_from!(windows::core::Error, Windows);
 derive_from!(FromUtf16Error, FromUtf16);
 
 pub type UiacResult<T> = Result<T, UiacError>;
 
+#[cfg(windows)]
 pub fn opt_result<T>(result: windows::core::Result<T>) -> windows::core::Result<Option<T>> {
     match result {
         Ok(t) => Ok(Some(t)),
@@ -0,0 +1,161 @@
+use {
+    crate::{
+        backend::{Element, TreeWalker},
+        matcher::{MatchFlags, MatchKind, Matcher},
+        property_value::value_text,
+        selector::{Attribute, Combinator, PseudoClass, Selector, Step},
+        IndexPath, Property, UiacError, UiacResult,
+    },
+    std::slice,
+};
+
+/// Computes a short selector that matches only the element at `path`, for use
+/// with `find --selector`.
+///
+/// The selector picks the element's top-level window from the desktop's
+/// children and then the element among the window's descendants. Each is
+/// identified by its `AutomationId` if that's unique, or else by its `Name` and
+/// control type, or its control type alone. Failing that, the element is
+/// anchored below a uniquely identified ancestor, taking the shortest such
+/// selector. As a last resort, it's picked out by its position among the
+/// elements that match below its nearest anchor, as in
+/// `> Window[Name="Calculator"] Group[AutomationId="NumberPad"] Button:nth(3)`.
+///
+/// `walker` should walk the raw view, which index paths count in.
+pub fn locate<W: TreeWalker>(
+    walker: &W,
+    desktop: &W::Element,
+    path: &IndexPath,
+) -> UiacResult<Selector> {
+    let (window_index, window_path) = path.0.split_first().ok_or_else(|| {
+        UiacError::Message("the desktop is where selectors start, so it has no locator".to_owned())
+    })?;
+
+    let mut steps = locate_below(walker, desktop, &[*window_index], Combinator::Child)?;
+    if !window_path.is_empty() {
+        let window = IndexPath(vec![*window_index]).resolve(walker, desktop)?;
+        steps.extend(locate_below(
+            walker,
+            &window,
+            window_path,
+            Combinator::Descendant,
+        )?);
+    }
+
+    Ok(Selector { steps })
+}
+
+/// Finds the steps that pick out the element at `path` below `root`, the first
+/// of which uses `combinator`.
+fn locate_below<W: TreeWalker>(
+    walker: &W,
+    root: &W::Element,
+    path: &[usize],
+    combinator: Combinator,
+) -> UiacResult<Vec<Step>> {
+    let target = IndexPath(path.to_vec()).resolve(walker, root)?;
+    let target_steps = identifying_steps(&target, combinator)?;
+
+    let matches = |steps: &[Step]| {
+        Selector {
+            steps: steps.to_vec(),
+        }
+        .find_all_paths(walker, root)
+    };
+    let is_unique = |steps: &[Step], path: &[usize]| -> UiacResult<bool> {
+        let matches = matches(steps)?;
+        Ok(matches.len() == 1 && matches.contains_key(path))
+    };
+
+    // The element on its own.
+    for step in &target_steps {
+        if is_unique(slice::from_ref(step), path)? {
+            return Ok(vec![step.clone()]);
+        }
+    }
+
+    // Anchored below an ancestor that's unique itself, nearest first.
+    let mut anchors = vec![];
+    if combinator == Combinator::Descendant {
+        for depth in (1..path.len()).rev() {
+            let ancestor = IndexPath(path[..depth].to_vec()).resolve(walker, root)?;
+            for step in identifying_steps(&ancestor, combinator)? {
+                if is_unique(slice::from_ref(&step), &path[..depth])? {
+                    anchors.push(step);
+                    break;
+                }
+            }
+        }
+    }
+    let mut candidates = vec![];
+    for anchor in &anchors {
+        for step in &target_steps {
+            let steps = vec![anchor.clone(), step.clone()];
+            if is_unique(&steps, path)? {
+                candidates.push(steps);
+            }
+        }
+    }
+    if let Some(steps) = shortest(candidates) {
+        return Ok(steps);
+    }
+
+    // Picked out by position among the fewest elements, i.e. below the nearest
+    // anchor and with the most specific step.
+    let mut steps: Vec<Step> = anchors.into_iter().take(1).collect();
+    steps.push(target_steps[0].clone());
+    let position = matches(&steps)?
+        .keys()
+        .position(|match_path| match_path == path)
+        .ok_or_else(|| {
+            UiacError::Message(format!(
+                "couldn't find a locator for {}",
+                IndexPath(path.to_vec())
+            ))
+        })?;
+    if let Some(step) = steps.last_mut() {
+        step.pseudo_classes.push(PseudoClass::Nth(position + 1));
+    }
+    Ok(steps)
+}
+
+/// The steps that could identify `element`, most preferred first: by
+/// `AutomationId`, by `Name`, and by control type alone.
+fn identifying_steps(element: &impl Element, combinator: Combinator) -> UiacResult<Vec<Step>> {
+    let control_type = element.get_current_control_type()?;
+    let step = |attributes| Step {
+        combinator,
+        control_type: Some(control_type),
+        attributes,
+        pseudo_classes: vec![],
+    };
+
+    let mut steps = vec![];
+    for property in [Property::AutomationId, Property::Name] {
+        let value = element.get_current_property_value(property)?;
+        if let Some(text) = value_text(property, &value).filter(|text| !text.is_empty()) {
+            steps.push(step(vec![Attribute {
+                property,
+                matcher: Some(Matcher::new(
+                    MatchKind::Equals,
+                    text,
+                    MatchFlags::default(),
+                )?),
+            }]));
+        }
+    }
+    steps.push(step(vec![]));
+
+    Ok(steps)
+}
+
+/// The candidate that reads shortest, or the earliest of those that tie.
+fn shortest(candidates: Vec<Vec<Step>>) -> Option<Vec<Step>> {
+    candidates.into_iter().min_by_key(|steps| {
+        Selector {
+            steps: steps.clone(),
+        }
+        .to_string()
+        .len()
+    })
+}
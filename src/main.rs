@@ -1,15 +1,22 @@
 use {
     clap::{self, Parser},
-    uiac::{dump, UiacResult},
-    windows::Win32::System::Com::{CoInitializeEx, COINIT_MULTITHREADED},
+    std::{io, path::PathBuf},
+    uiac::{
+        backend::Automation, breadcrumb, dump, find, locate, memory, resolve, selector::Selector,
+        snapshot::Snapshot, xpath::XPath, DumpOptions, Filter, FindOptions, Format, IndexPath,
+        MatchFlags, MatchKind, Matcher, Order, Property, PropertyList, PropertyValue,
+        ResolveOptions, Root, TreeScope, UiacError, UiacResult, View,
+    },
 };
 
-#[cfg(not(target_os = "windows"))]
-compile_error!("uiac is only supported on Windows.");
-
 #[derive(Parser)]
 #[command(author, version, about)]
 struct Opts {
+    /// Read the UIA tree from a snapshot captured with `dump --format json`
+    /// instead of the current desktop.
+    #[arg(long, global = true, value_name = "FILE")]
+    from_snapshot: Option<PathBuf>,
+
     #[command(subcommand)]
     subcommand: Subcommand,
 }
@@ -17,16 +24,437 @@ struct Opts {
 #[derive(clap::Subcommand)]
 enum Subcommand {
     /// Dump the UIA tree on the current desktop.
-    Dump,
+    Dump {
+        /// The output format.
+        #[arg(long, value_enum, default_value_t)]
+        format: Format,
+
+        /// A comma-separated list of the properties to print for each element,
+        /// or "all".
+        #[arg(long, default_value = "Name,ControlType")]
+        properties: PropertyList,
+
+        /// Stop descending after this many levels below the root.
+        #[arg(long, value_name = "N")]
+        max_depth: Option<usize>,
+
+        /// Which view of the tree to walk.
+        #[arg(long, value_enum, default_value_t)]
+        view: View,
+
+        /// Only print elements matching a condition such as
+        /// `ControlType=Button and not IsEnabled=true`. May be given more than
+        /// once to require several conditions. The root is always printed.
+        #[arg(long, value_name = "CONDITION")]
+        condition: Vec<Filter>,
+
+        /// Print each element's index path, which `resolve` and `--root-path`
+        /// take.
+        #[arg(long)]
+        paths: bool,
+
+        /// The order to print elements in. JSON output always nests them.
+        #[arg(long, value_enum, default_value_t)]
+        order: Order,
+
+        /// With `--format dot`, fill each node with a colour for its control
+        /// type.
+        #[arg(long)]
+        color_by_control_type: bool,
+
+        #[command(flatten)]
+        root: RootArgs,
+    },
+    /// Print the properties of the element at an index path, as printed by
+    /// `dump --paths`. Properties the element doesn't support are left out.
+    Resolve {
+        /// The index path of the element, such as 0/3/1/4.
+        #[arg(value_name = "PATH")]
+        path: IndexPath,
+
+        /// The output format.
+        #[arg(long, value_enum, default_value_t)]
+        format: Format,
+
+        /// A comma-separated list of the properties to print, or "all".
+        #[arg(long, default_value = "all")]
+        properties: PropertyList,
+    },
+    /// Find the elements that match a condition and print them.
+    Find {
+        /// The condition elements must match, such as
+        /// `ControlType=Button and Name~="ok"`. Several conditions must all
+        /// match.
+        #[arg(required_unless_present = "selector", value_name = "CONDITION")]
+        condition: Vec<Filter>,
+
+        /// Which elements around the root to search.
+        #[arg(long, value_enum, default_value_t)]
+        scope: TreeScope,
+
+        /// Find elements with a CSS-like selector such as
+        /// `Window[Name="Notepad"] > Pane Edit[AutomationId="15"]:nth(2)`
+        /// instead of a condition.
+        #[arg(long, conflicts_with_all = ["condition", "scope"])]
+        selector: Option<Selector>,
+
+        /// Stop at the first match, and fail if there isn't one.
+        #[arg(long)]
+        first: bool,
+
+        /// Print only the number of matches.
+        #[arg(long)]
+        count: bool,
+
+        /// The output format.
+        #[arg(long, value_enum, default_value_t)]
+        format: Format,
+
+        /// A comma-separated list of the properties to print for each element,
+        /// or "all".
+        #[arg(long, default_value = "Name,ControlType")]
+        properties: PropertyList,
+
+        /// How many levels below each match to print.
+        #[arg(long, value_name = "N", default_value_t = 0)]
+        max_depth: usize,
+
+        /// Which view of the tree to search.
+        #[arg(long, value_enum, default_value_t)]
+        view: View,
+
+        #[command(flatten)]
+        root: RootArgs,
+    },
+    /// Evaluate an XPath such as `//Window[@Name='Calculator']//Button` and
+    /// print the elements or values it selects.
+    Xpath {
+        /// The XPath, with control types as element names and properties as
+        /// attributes.
+        #[arg(value_name = "EXPR")]
+        xpath: XPath,
+
+        /// Stop at the first match, and fail if there isn't one.
+        #[arg(long)]
+        first: bool,
+
+        /// Print only the number of matches.
+        #[arg(long)]
+        count: bool,
+
+        /// The output format.
+        #[arg(long, value_enum, default_value_t)]
+        format: Format,
+
+        /// A comma-separated list of the properties to print for each element,
+        /// or "all".
+        #[arg(long, default_value = "Name,ControlType")]
+        properties: PropertyList,
+
+        /// How many levels below each match to print.
+        #[arg(long, value_name = "N", default_value_t = 0)]
+        max_depth: usize,
+
+        /// Which view of the tree to search.
+        #[arg(long, value_enum, default_value_t)]
+        view: View,
+
+        #[command(flatten)]
+        root: RootArgs,
+    },
+    /// Print a selector that matches only the given element, for use with
+    /// `find --selector`.
+    #[command(group = clap::ArgGroup::new("element").required(true))]
+    Locate {
+        /// The index path of the element, such as 0/3/1/4.
+        #[arg(group = "element", value_name = "PATH")]
+        path: Option<IndexPath>,
+
+        /// Locate the element with keyboard focus.
+        #[arg(long, group = "element")]
+        focused: bool,
+
+        /// Locate the element at a point on the screen, given in physical
+        /// pixels as X,Y.
+        #[arg(long, group = "element", value_name = "X,Y", value_parser = parse_point)]
+        point: Option<(i32, i32)>,
+    },
+    /// Print where an element is in the tree, from the root down, such as
+    /// `Pane "Desktop 1" > Pane "Taskbar" > Button "Start"`. By default, this
+    /// is the element with keyboard focus.
+    Breadcrumb {
+        /// The index path of the element, such as 0/3/1/4.
+        #[arg(value_name = "PATH", conflicts_with = "point")]
+        path: Option<IndexPath>,
+
+        /// Show the element at a point on the screen, given in physical pixels
+        /// as X,Y.
+        #[arg(long, value_name = "X,Y", value_parser = parse_point)]
+        point: Option<(i32, i32)>,
+
+        /// Which view of the tree to show the element's ancestors in.
+        #[arg(long, value_enum, default_value_t)]
+        view: View,
+    },
 }
 
-fn main() -> UiacResult<()> {
-    unsafe { CoInitializeEx(None, COINIT_MULTITHREADED) }?;
+/// Options for choosing where to start walking the tree. By default, the walk
+/// starts at the desktop.
+#[derive(clap::Args)]
+struct RootArgs {
+    /// Start at the top-level window whose name matches this glob, in
+    /// which `*` matches any text and `?` any one character, such as
+    /// "* - Word".
+    #[arg(
+        long,
+        value_name = "NAME",
+        conflicts_with_all = ["focused", "root_query", "root_path"],
+    )]
+    window_name: Option<String>,
+
+    /// Start at the top-level window whose class name matches this glob.
+    #[arg(
+        long,
+        value_name = "CLASS",
+        conflicts_with_all = ["focused", "root_query", "root_path"],
+    )]
+    window_class: Option<String>,
+
+    /// Start at the top-level window owned by this process.
+    #[arg(
+        long,
+        value_name = "PID",
+        conflicts_with_all = ["focused", "root_query", "root_path"],
+    )]
+    window_pid: Option<i32>,
+
+    /// Start at the top-level window with this native window handle, in
+    /// decimal or hex with a "0x" prefix.
+    #[arg(
+        long,
+        value_name = "HWND",
+        value_parser = parse_window_handle,
+        conflicts_with_all = ["focused", "root_query", "root_path"],
+    )]
+    window_handle: Option<PropertyValue>,
+
+    /// Start at the element with keyboard focus.
+    #[arg(long, conflicts_with_all = ["root_query", "root_path"])]
+    focused: bool,
+
+    /// Start at the first element matching a condition, such as
+    /// AutomationId=15 or `Name?="* - Word"`. Several conditions must all
+    /// match.
+    #[arg(long, value_name = "CONDITION", conflicts_with = "root_path")]
+    root_query: Vec<Filter>,
+
+    /// Start at the element at this index path, as printed by `dump --paths`.
+    #[arg(long, value_name = "PATH")]
+    root_path: Option<IndexPath>,
+}
+
+impl RootArgs {
+    fn into_root(self) -> UiacResult<Root> {
+        let glob = |property, glob| -> UiacResult<Filter> {
+            Ok(Filter::matching(
+                property,
+                Matcher::new(MatchKind::Glob, glob, MatchFlags::default())?,
+            ))
+        };
+        let window_filters: Vec<Filter> = [
+            self.window_name.map(|name| glob(Property::Name, name)),
+            self.window_class
+                .map(|class| glob(Property::ClassName, class)),
+            self.window_pid.map(|pid| {
+                Ok(Filter::property(
+                    Property::ProcessId,
+                    PropertyValue::Int(pid),
+                ))
+            }),
+            self.window_handle
+                .map(|handle| Ok(Filter::property(Property::NativeWindowHandle, handle))),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<UiacResult<_>>()?;
 
+        Ok(if !window_filters.is_empty() {
+            Root::Window(Filter::all(window_filters))
+        } else if self.focused {
+            Root::Focused
+        } else if !self.root_query.is_empty() {
+            Root::Query(Filter::all(self.root_query))
+        } else if let Some(path) = self.root_path {
+            Root::Path(path)
+        } else {
+            Root::Desktop
+        })
+    }
+}
+
+fn parse_window_handle(s: &str) -> UiacResult<PropertyValue> {
+    Property::NativeWindowHandle.parse_value(s)
+}
+
+fn parse_point(s: &str) -> UiacResult<(i32, i32)> {
+    let error = || UiacError::Message(format!("expected a point as X,Y, got {s:?}"));
+    let (x, y) = s.split_once(',').ok_or_else(error)?;
+    Ok((
+        x.trim().parse().map_err(|_| error())?,
+        y.trim().parse().map_err(|_| error())?,
+    ))
+}
+
+fn main() -> UiacResult<()> {
     let opts = Opts::parse();
-    match opts.subcommand {
-        Subcommand::Dump => dump(),
-    }?;
 
-    Ok(())
+    match opts.from_snapshot {
+        Some(path) => {
+            let automation = memory::Automation::from_snapshot(Snapshot::from_file(path)?);
+            run(&automation, opts.subcommand)
+        }
+        #[cfg(windows)]
+        None => run(&live_automation()?, opts.subcommand),
+        #[cfg(not(windows))]
+        None => Err(UiacError::Message(
+            "uiac can only inspect a live desktop on Windows; use --from-snapshot instead"
+                .to_owned(),
+        )),
+    }
+}
+
+#[cfg(windows)]
+fn live_automation() -> UiacResult<uiac::wrappers::Automation> {
+    use windows::Win32::System::Com::{CoInitializeEx, COINIT_MULTITHREADED};
+
+    unsafe { CoInitializeEx(None, COINIT_MULTITHREADED) }?;
+    uiac::wrappers::Automation::new()
+}
+
+fn run<A: Automation>(automation: &A, subcommand: Subcommand) -> UiacResult<()> {
+    match subcommand {
+        Subcommand::Dump {
+            format,
+            properties,
+            max_depth,
+            view,
+            condition,
+            paths,
+            order,
+            color_by_control_type,
+            root,
+        } => dump(
+            automation,
+            &DumpOptions {
+                format,
+                properties: properties.0,
+                root: root.into_root()?,
+                max_depth,
+                view,
+                condition: Filter::all(condition),
+                paths,
+                order,
+                color_by_control_type,
+            },
+            &mut io::stdout().lock(),
+        ),
+        Subcommand::Resolve {
+            path,
+            format,
+            properties,
+        } => resolve(
+            automation,
+            &ResolveOptions {
+                format,
+                path,
+                properties: properties.0,
+            },
+            &mut io::stdout().lock(),
+        ),
+        Subcommand::Find {
+            condition,
+            scope,
+            selector,
+            first,
+            count,
+            format,
+            properties,
+            max_depth,
+            view,
+            root,
+        } => find(
+            automation,
+            &FindOptions {
+                format,
+                properties: properties.0,
+                root: root.into_root()?,
+                scope,
+                view,
+                condition: Filter::all(condition),
+                selector,
+                xpath: None,
+                first,
+                count,
+                max_depth: Some(max_depth),
+            },
+            &mut io::stdout().lock(),
+        ),
+        Subcommand::Xpath {
+            xpath,
+            first,
+            count,
+            format,
+            properties,
+            max_depth,
+            view,
+            root,
+        } => find(
+            automation,
+            &FindOptions {
+                format,
+                properties: properties.0,
+                root: root.into_root()?,
+                view,
+                xpath: Some(xpath),
+                first,
+                count,
+                max_depth: Some(max_depth),
+                ..FindOptions::default()
+            },
+            &mut io::stdout().lock(),
+        ),
+        Subcommand::Locate {
+            path,
+            focused,
+            point,
+        } => {
+            let path = match (path, focused, point) {
+                (Some(path), _, _) => path,
+                (_, true, _) => IndexPath::of(automation, &automation.get_focused_element()?)?,
+                (_, _, Some((x, y))) => {
+                    IndexPath::of(automation, &automation.get_element_from_point(x, y)?)?
+                }
+                _ => unreachable!("clap requires one of the element options"),
+            };
+            let walker = automation.create_tree_walker(&automation.get_raw_view_condition()?)?;
+            let selector = locate(&walker, &automation.get_root_element()?, &path)?;
+            println!("{selector}");
+            Ok(())
+        }
+        Subcommand::Breadcrumb { path, point, view } => {
+            let element = match (path, point) {
+                (Some(path), _) => path.resolve(
+                    &View::Raw.walker(automation)?,
+                    &automation.get_root_element()?,
+                )?,
+                (_, Some((x, y))) => automation.get_element_from_point(x, y)?,
+                _ => automation.get_focused_element()?,
+            };
+            println!(
+                "{}",
+                breadcrumb(automation, &view.walker(automation)?, &element)?
+            );
+            Ok(())
+        }
+    }
 }
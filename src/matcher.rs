@@ -0,0 +1,333 @@
+use {
+    crate::{UiacError, UiacResult},
+    regex::{Regex, RegexBuilder},
+};
+
+/// How a `Matcher` compares text with its pattern.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatchKind {
+    Equals,
+    Contains,
+    StartsWith,
+    EndsWith,
+    /// A shell-style pattern, where `*` matches any text, `?` any single
+    /// character and `[...]` or `[!...]` a set of characters.
+    Glob,
+    /// A regular expression, which matches anywhere in the text unless
+    /// anchored.
+    Regex,
+}
+
+/// How a `Matcher` prepares text before comparing it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MatchFlags {
+    pub ignore_case: bool,
+    /// Trim the text and collapse runs of whitespace into single spaces, so
+    /// "Document1 -  Word\n" matches "Document1 - Word".
+    pub normalize_whitespace: bool,
+}
+
+/// A test of a property's text that runs in uiac rather than in UI Automation,
+/// for the comparisons UIA's property conditions can't make.
+#[derive(Clone, Debug)]
+pub struct Matcher {
+    kind: MatchKind,
+    pattern: String,
+    flags: MatchFlags,
+    regex: Regex,
+}
+
+impl Matcher {
+    pub fn new(kind: MatchKind, pattern: impl Into<String>, flags: MatchFlags) -> UiacResult<Self> {
+        let pattern = pattern.into();
+
+        let literal = |pattern: &str| {
+            if flags.normalize_whitespace {
+                regex::escape(&normalize_whitespace(pattern))
+            } else {
+                regex::escape(pattern)
+            }
+        };
+        let regex = match kind {
+            MatchKind::Equals => format!("^{}$", literal(&pattern)),
+            MatchKind::Contains => literal(&pattern),
+            MatchKind::StartsWith => format!("^{}", literal(&pattern)),
+            MatchKind::EndsWith => format!("{}$", literal(&pattern)),
+            MatchKind::Glob => format!("^{}$", glob_to_regex(&pattern)),
+            MatchKind::Regex => pattern.clone(),
+        };
+        let regex = RegexBuilder::new(&regex)
+            .case_insensitive(flags.ignore_case)
+            .dot_matches_new_line(kind == MatchKind::Glob)
+            .build()
+            .map_err(|err| UiacError::Message(format!("invalid pattern {pattern:?}: {err}")))?;
+
+        Ok(Self {
+            kind,
+            pattern,
+            flags,
+            regex,
+        })
+    }
+
+    pub fn kind(&self) -> MatchKind {
+        self.kind
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn flags(&self) -> MatchFlags {
+        self.flags
+    }
+
+    /// The operator that filters and selectors write the matcher with, such
+    /// as `^~=`. See `parse_operator`.
+    pub(crate) fn operator(&self) -> String {
+        let kind = match self.kind {
+            MatchKind::Equals => "",
+            MatchKind::Contains => "*",
+            MatchKind::StartsWith => "^",
+            MatchKind::EndsWith => "$",
+            MatchKind::Glob => "?",
+            MatchKind::Regex => "/",
+        };
+        format!(
+            "{kind}{}{}=",
+            if self.flags.ignore_case { "~" } else { "" },
+            if self.flags.normalize_whitespace {
+                "_"
+            } else {
+                ""
+            },
+        )
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        if self.flags.normalize_whitespace {
+            self.regex.is_match(&normalize_whitespace(text))
+        } else {
+            self.regex.is_match(text)
+        }
+    }
+}
+
+impl PartialEq for Matcher {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind && self.pattern == other.pattern && self.flags == other.flags
+    }
+}
+
+/// The characters an operator is made of, before its `=`.
+pub(crate) const OPERATOR_CHARS: &str = "*^$?/~_";
+
+/// Reads the part of an operator before its `=`: an optional kind (`*`
+/// contains, `^` starts with, `$` ends with, `?` glob or `/` regex), then `~`
+/// to ignore case and then `_` to normalize whitespace, each optional. Filters
+/// and selectors share these, so an operator means the same in both.
+pub(crate) fn parse_operator(operator: &str) -> Option<(MatchKind, MatchFlags)> {
+    let (kind, modifiers) = match operator.chars().next() {
+        Some('*') => (MatchKind::Contains, &operator[1..]),
+        Some('^') => (MatchKind::StartsWith, &operator[1..]),
+        Some('$') => (MatchKind::EndsWith, &operator[1..]),
+        Some('?') => (MatchKind::Glob, &operator[1..]),
+        Some('/') => (MatchKind::Regex, &operator[1..]),
+        _ => (MatchKind::Equals, operator),
+    };
+    let flags = match modifiers {
+        "" => MatchFlags::default(),
+        "~" => MatchFlags {
+            ignore_case: true,
+            ..MatchFlags::default()
+        },
+        "_" => MatchFlags {
+            normalize_whitespace: true,
+            ..MatchFlags::default()
+        },
+        "~_" => MatchFlags {
+            ignore_case: true,
+            normalize_whitespace: true,
+        },
+        _ => return None,
+    };
+    Some((kind, flags))
+}
+
+fn normalize_whitespace(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Translates a glob into an unanchored regular expression. A `[` without a
+/// closing `]` matches itself.
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::new();
+    let mut chars = glob.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            '[' => {
+                let rest: String = chars.clone().collect();
+                // A "]" straight after the "[" or "[!" is part of the set.
+                let set_start = usize::from(rest.starts_with('!'));
+                let set_end = rest
+                    .char_indices()
+                    .skip(set_start + 1)
+                    .find(|(_, c)| *c == ']')
+                    .map(|(i, _)| i);
+                match set_end {
+                    Some(set_end) => {
+                        regex.push('[');
+                        if set_start == 1 {
+                            regex.push('^');
+                        }
+                        for c in rest[set_start..set_end].chars() {
+                            // Escape everything the regex crate treats
+                            // specially in a class except ranges.
+                            if matches!(c, '\\' | '[' | ']' | '^' | '&' | '~') {
+                                regex.push('\\');
+                            }
+                            regex.push(c);
+                        }
+                        regex.push(']');
+                        for _ in rest[..=set_end].chars() {
+                            chars.next();
+                        }
+                    }
+                    None => regex.push_str(r"\["),
+                }
+            }
+            c => regex.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    regex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matcher_test() -> UiacResult<()> {
+        let flags = MatchFlags::default();
+        let ignore_case = MatchFlags {
+            ignore_case: true,
+            ..MatchFlags::default()
+        };
+        let normalize_whitespace = MatchFlags {
+            normalize_whitespace: true,
+            ..MatchFlags::default()
+        };
+
+        let cases = [
+            (MatchKind::Equals, "a.b", flags, "a.b", true),
+            (MatchKind::Equals, "a.b", flags, "axb", false),
+            (MatchKind::Equals, "A.B", ignore_case, "a.b", true),
+            (
+                MatchKind::Equals,
+                "Document1 -  Word",
+                normalize_whitespace,
+                " Document1 - Word\n",
+                true,
+            ),
+            (
+                MatchKind::Contains,
+                "1 - W",
+                flags,
+                "Document1 - Word",
+                true,
+            ),
+            (
+                MatchKind::StartsWith,
+                "Doc",
+                flags,
+                "Document1 - Word",
+                true,
+            ),
+            (
+                MatchKind::StartsWith,
+                "Word",
+                flags,
+                "Document1 - Word",
+                false,
+            ),
+            (
+                MatchKind::EndsWith,
+                " - word",
+                ignore_case,
+                "Document1 - Word",
+                true,
+            ),
+            (MatchKind::Glob, "* - Word", flags, "Document1 - Word", true),
+            (
+                MatchKind::Glob,
+                "* - Word",
+                flags,
+                "Document1 - Word 2",
+                false,
+            ),
+            (
+                MatchKind::Glob,
+                "Document? - *",
+                flags,
+                "Document1 - Word",
+                true,
+            ),
+            (
+                MatchKind::Glob,
+                "Document[0-9] *",
+                flags,
+                "Document1 - Word",
+                true,
+            ),
+            (
+                MatchKind::Glob,
+                "Document[!0-9] *",
+                flags,
+                "Document1 - Word",
+                false,
+            ),
+            (MatchKind::Glob, "[*", flags, "[abc", true),
+            (MatchKind::Glob, "a.b", flags, "axb", false),
+            (
+                MatchKind::Regex,
+                r"^Document\d+ - Word$",
+                flags,
+                "Document12 - Word",
+                true,
+            ),
+            (MatchKind::Regex, r"\d", flags, "Word", false),
+            (
+                MatchKind::Regex,
+                "word",
+                ignore_case,
+                "Document1 - Word",
+                true,
+            ),
+        ];
+
+        for (kind, pattern, flags, text, expected) in cases {
+            assert_eq!(
+                Matcher::new(kind, pattern, flags)?.is_match(text),
+                expected,
+                "{kind:?} {pattern:?} {flags:?} against {text:?}"
+            );
+        }
+
+        assert!(Matcher::new(MatchKind::Regex, "(", flags).is_err());
+
+        for operator in ["", "*", "^~", "$_", "?~_", "/~"] {
+            let (kind, flags) = parse_operator(operator).unwrap();
+            assert_eq!(
+                Matcher::new(kind, "", flags)?.operator(),
+                format!("{operator}=")
+            );
+        }
+        for operator in ["**", "_~", "~*", "!"] {
+            assert_eq!(parse_operator(operator), None, "{operator:?}");
+        }
+
+        Ok(())
+    }
+}
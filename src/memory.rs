@@ -0,0 +1,782 @@
+use {
+    crate::{
+        backend,
+        snapshot::{self, Snapshot},
+        ControlType, Filter, IndexPath, Property, PropertyConditionFlags, PropertyValue, TreeScope,
+        UiacError, UiacResult, View,
+    },
+    serde::{Deserialize, Serialize},
+    std::{collections::BTreeMap, fs, mem, path::Path, rc::Rc},
+};
+
+/// A declarative description of an element and its subtree, as found in a
+/// fixture or snapshot file.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Node {
+    #[serde(default)]
+    pub name: String,
+    pub control_type: ControlType,
+    /// The node's index path, if it was captured with `dump --paths`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<IndexPath>,
+    #[serde(default)]
+    pub properties: BTreeMap<Property, PropertyValue>,
+    #[serde(default)]
+    pub children: Vec<Node>,
+}
+
+impl Drop for Node {
+    /// Moves each node's children onto a stack before the node is dropped, so
+    /// every node goes with no children left to drop.
+    fn drop(&mut self) {
+        let mut stack = mem::take(&mut self.children);
+        while let Some(mut node) = stack.pop() {
+            stack.append(&mut node.children);
+        }
+    }
+}
+
+struct NodeData {
+    name: String,
+    control_type: ControlType,
+    properties: BTreeMap<Property, PropertyValue>,
+    parent: Option<usize>,
+    first_child: Option<usize>,
+    last_child: Option<usize>,
+    next_sibling: Option<usize>,
+    previous_sibling: Option<usize>,
+}
+
+/// Which way to go through a node's children.
+#[derive(Clone, Copy)]
+enum Direction {
+    Forward,
+    Backward,
+}
+
+impl NodeData {
+    /// The first child in `direction`.
+    fn end_child(&self, direction: Direction) -> Option<usize> {
+        match direction {
+            Direction::Forward => self.first_child,
+            Direction::Backward => self.last_child,
+        }
+    }
+
+    /// The next sibling in `direction`.
+    fn sibling(&self, direction: Direction) -> Option<usize> {
+        match direction {
+            Direction::Forward => self.next_sibling,
+            Direction::Backward => self.previous_sibling,
+        }
+    }
+
+    /// Properties missing from the node are reported as not supported, except
+    /// for the view properties, which default to true as they do in UIA.
+    fn property_value(&self, property: Property) -> PropertyValue {
+        match property {
+            Property::Name => PropertyValue::String(self.name.clone()),
+            Property::ControlType => PropertyValue::Int(self.control_type.id() as i32),
+            _ => match self.properties.get(&property) {
+                Some(value) => value.clone(),
+                None if matches!(
+                    property,
+                    Property::IsControlElement | Property::IsContentElement
+                ) =>
+                {
+                    PropertyValue::Bool(true)
+                }
+                None => PropertyValue::NotSupported,
+            },
+        }
+    }
+}
+
+/// The nodes of a tree, flattened in depth-first order so that elements can
+/// refer to them by index.
+struct Tree {
+    nodes: Vec<NodeData>,
+}
+
+impl Tree {
+    fn new(root: Node) -> Self {
+        let mut nodes: Vec<NodeData> = vec![];
+
+        // Each node popped is added after its parent's last child, and its own
+        // children are pushed in reverse, so the first of them is added next.
+        let mut stack = vec![(root, None)];
+        while let Some((mut node, parent)) = stack.pop() {
+            let index = nodes.len();
+            let previous_sibling = parent.and_then(|parent: usize| nodes[parent].last_child);
+            nodes.push(NodeData {
+                name: mem::take(&mut node.name),
+                control_type: node.control_type,
+                properties: mem::take(&mut node.properties),
+                parent,
+                first_child: None,
+                last_child: None,
+                next_sibling: None,
+                previous_sibling,
+            });
+
+            if let Some(parent) = parent {
+                match previous_sibling {
+                    Some(previous_sibling) => nodes[previous_sibling].next_sibling = Some(index),
+                    None => nodes[parent].first_child = Some(index),
+                }
+                nodes[parent].last_child = Some(index);
+            }
+
+            stack.extend(
+                mem::take(&mut node.children)
+                    .into_iter()
+                    .rev()
+                    .map(|child| (child, Some(index))),
+            );
+        }
+
+        Tree { nodes }
+    }
+
+    fn matches(&self, index: usize, condition: &Condition) -> bool {
+        // Reading a node's properties can't fail.
+        matches!(
+            condition.evaluate(&mut |property| Ok(self.nodes[index].property_value(property))),
+            Ok(true)
+        )
+    }
+
+    /// The children of a node, in order.
+    fn children(&self, index: usize) -> Vec<usize> {
+        let mut children = vec![];
+        let mut child_option = self.nodes[index].first_child;
+        while let Some(child) = child_option {
+            children.push(child);
+            child_option = self.nodes[child].next_sibling;
+        }
+        children
+    }
+
+    /// The deepest node below `index` whose rectangle contains the point.
+    /// Later siblings are on top, so they're tried first. Nodes without a
+    /// rectangle are looked inside, and the search goes into the first node
+    /// found to contain the point, never to come back out.
+    fn element_at(&self, index: usize, x: f64, y: f64) -> Option<usize> {
+        let mut found = None;
+        // The nodes to try, last first. Looking inside one replaces the stack
+        // with its children.
+        let mut stack = self.children(index);
+        while let Some(child) = stack.pop() {
+            match self.nodes[child]
+                .properties
+                .get(&Property::BoundingRectangle)
+            {
+                Some(PropertyValue::DoubleArray(rect)) if rect.len() == 4 => {
+                    let contains = (rect[0]..rect[0] + rect[2]).contains(&x)
+                        && (rect[1]..rect[1] + rect[3]).contains(&y);
+                    if contains {
+                        found = Some(child);
+                        stack = self.children(child);
+                    }
+                }
+                _ => stack.extend(self.children(child)),
+            }
+        }
+        found
+    }
+
+    /// The index just past the last node in `index`'s subtree, which is
+    /// contiguous because nodes are stored in depth-first order.
+    fn subtree_end(&self, index: usize) -> usize {
+        let mut current = index;
+        loop {
+            let node = &self.nodes[current];
+            match (node.next_sibling, node.parent) {
+                (Some(next_sibling), _) => return next_sibling,
+                (None, Some(parent)) => current = parent,
+                _ => return self.nodes.len(),
+            }
+        }
+    }
+
+    /// The nodes in `scope` relative to `index`, in depth-first order.
+    fn scope(&self, index: usize, scope: TreeScope) -> Vec<usize> {
+        match scope {
+            TreeScope::Children => self.children(index),
+            TreeScope::Descendants => (index + 1..self.subtree_end(index)).collect(),
+            TreeScope::Subtree => (index..self.subtree_end(index)).collect(),
+        }
+    }
+
+    /// Finds the parent of a node in the view defined by `condition`: its
+    /// nearest matching ancestor, or the root, which is always part of the
+    /// view.
+    fn parent(&self, index: usize, condition: &Condition) -> Option<usize> {
+        let mut current = self.nodes[index].parent?;
+        while current != 0 && !self.matches(current, condition) {
+            current = self.nodes[current].parent?;
+        }
+        Some(current)
+    }
+
+    /// Finds the element itself if it's in the view defined by `condition`, or
+    /// else its parent in the view.
+    fn normalize(&self, index: usize, condition: &Condition) -> Option<usize> {
+        if index == 0 || self.matches(index, condition) {
+            Some(index)
+        } else {
+            self.parent(index, condition)
+        }
+    }
+
+    /// Finds the first child of a node in `direction` in the view defined by
+    /// `condition`: the first matching node below it, skipping over the
+    /// subtrees of matches. The search goes down into nodes that don't match
+    /// and along to their next siblings, climbing back up when a level runs
+    /// out.
+    fn first_child(
+        &self,
+        index: usize,
+        condition: &Condition,
+        direction: Direction,
+    ) -> Option<usize> {
+        let mut current = self.nodes[index].end_child(direction)?;
+        loop {
+            if self.matches(current, condition) {
+                return Some(current);
+            }
+            if let Some(child) = self.nodes[current].end_child(direction) {
+                current = child;
+                continue;
+            }
+
+            // Go along, climbing back up until there's a sibling, but not out
+            // of the node's subtree.
+            loop {
+                if let Some(sibling) = self.nodes[current].sibling(direction) {
+                    current = sibling;
+                    break;
+                }
+                current = self.nodes[current].parent?;
+                if current == index {
+                    return None;
+                }
+            }
+        }
+    }
+
+    /// Finds the next sibling of a node in `direction` in the view defined by
+    /// `condition`, climbing out of non-matching ancestors until it reaches one
+    /// that's part of the view, or the root.
+    fn next_sibling(
+        &self,
+        index: usize,
+        condition: &Condition,
+        direction: Direction,
+    ) -> Option<usize> {
+        let mut current = index;
+        loop {
+            let mut sibling_option = self.nodes[current].sibling(direction);
+            while let Some(sibling) = sibling_option {
+                if self.matches(sibling, condition) {
+                    return Some(sibling);
+                }
+                if let Some(found) = self.first_child(sibling, condition, direction) {
+                    return Some(found);
+                }
+                sibling_option = self.nodes[sibling].sibling(direction);
+            }
+
+            match self.nodes[current].parent {
+                Some(parent) if parent != 0 && !self.matches(parent, condition) => current = parent,
+                _ => return None,
+            }
+        }
+    }
+}
+
+/// A backend that serves a fixed tree of elements held in memory.
+pub struct Automation {
+    tree: Rc<Tree>,
+}
+
+impl Automation {
+    pub fn new(root: Node) -> Self {
+        Self {
+            tree: Rc::new(Tree::new(root)),
+        }
+    }
+
+    pub fn from_json(json: &str) -> UiacResult<Self> {
+        Ok(Self::new(snapshot::read_json(json)?))
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> UiacResult<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Serves the tree recorded in a snapshot, as if it were a live desktop.
+    pub fn from_snapshot(snapshot: Snapshot) -> Self {
+        Self::new(snapshot.tree)
+    }
+}
+
+impl backend::Automation for Automation {
+    type Element = Element;
+    type TreeWalker = TreeWalker;
+    type Condition = Condition;
+    type CacheRequest = CacheRequest;
+
+    fn get_root_element(&self) -> UiacResult<Element> {
+        Ok(Element::new(&self.tree, 0))
+    }
+
+    /// The focused element is the first one whose `HasKeyboardFocus` property
+    /// is true.
+    fn get_focused_element(&self) -> UiacResult<Element> {
+        let index = self
+            .tree
+            .nodes
+            .iter()
+            .position(|node| {
+                node.properties.get(&Property::HasKeyboardFocus) == Some(&PropertyValue::Bool(true))
+            })
+            .ok_or_else(|| UiacError::Message("no element has keyboard focus".to_owned()))?;
+
+        Ok(Element::new(&self.tree, index))
+    }
+
+    /// The element at a point is the deepest one whose `BoundingRectangle`
+    /// contains it, preferring later siblings as they're drawn on top. Elements
+    /// without a rectangle don't hide their descendants.
+    fn get_element_from_point(&self, x: i32, y: i32) -> UiacResult<Element> {
+        Ok(Element::new(
+            &self.tree,
+            self.tree
+                .element_at(0, f64::from(x), f64::from(y))
+                .unwrap_or(0),
+        ))
+    }
+
+    fn compare_elements(&self, element1: &Element, element2: &Element) -> UiacResult<bool> {
+        Ok(Rc::ptr_eq(&element1.tree, &element2.tree) && element1.index == element2.index)
+    }
+
+    fn create_tree_walker(&self, condition: &Condition) -> UiacResult<TreeWalker> {
+        Ok(TreeWalker {
+            condition: condition.clone(),
+        })
+    }
+
+    fn create_cache_request(&self, properties: &[Property]) -> UiacResult<CacheRequest> {
+        Ok(CacheRequest {
+            properties: properties.to_vec(),
+        })
+    }
+
+    fn create_true_condition(&self) -> UiacResult<Condition> {
+        Ok(Condition::True)
+    }
+
+    fn create_false_condition(&self) -> UiacResult<Condition> {
+        Ok(Condition::False)
+    }
+
+    fn create_property_condition_ex(
+        &self,
+        property: Property,
+        value: &PropertyValue,
+        flags: PropertyConditionFlags,
+    ) -> UiacResult<Condition> {
+        Ok(Filter::property_ex(property, value.clone(), flags))
+    }
+
+    fn create_and_condition(
+        &self,
+        condition1: &Condition,
+        condition2: &Condition,
+    ) -> UiacResult<Condition> {
+        Ok(condition1.clone().and(condition2.clone()))
+    }
+
+    fn create_or_condition(
+        &self,
+        condition1: &Condition,
+        condition2: &Condition,
+    ) -> UiacResult<Condition> {
+        Ok(condition1.clone().or(condition2.clone()))
+    }
+
+    fn create_not_condition(&self, condition: &Condition) -> UiacResult<Condition> {
+        Ok(!condition.clone())
+    }
+
+    fn get_raw_view_condition(&self) -> UiacResult<Condition> {
+        Ok(Condition::True)
+    }
+
+    fn get_control_view_condition(&self) -> UiacResult<Condition> {
+        Ok(View::Control.filter())
+    }
+
+    fn get_content_view_condition(&self) -> UiacResult<Condition> {
+        Ok(View::Content.filter())
+    }
+}
+
+#[derive(Clone)]
+pub struct Element {
+    tree: Rc<Tree>,
+    index: usize,
+    /// The properties read when the element was fetched with a cache request.
+    cache: Option<Rc<BTreeMap<Property, PropertyValue>>>,
+}
+
+impl Element {
+    fn new(tree: &Rc<Tree>, index: usize) -> Self {
+        Self {
+            tree: Rc::clone(tree),
+            index,
+            cache: None,
+        }
+    }
+
+    fn data(&self) -> &NodeData {
+        &self.tree.nodes[self.index]
+    }
+
+    fn with_index(&self, index: Option<usize>) -> Option<Element> {
+        index.map(|index| Element::new(&self.tree, index))
+    }
+
+    /// Like `with_index`, but filling the cache as `cache_request` asks.
+    fn with_index_cached(
+        &self,
+        index: Option<usize>,
+        cache_request: &CacheRequest,
+    ) -> Option<Element> {
+        self.with_index(index).map(|mut element| {
+            let data = element.data();
+            let cache = cache_request
+                .properties
+                .iter()
+                .map(|property| (*property, data.property_value(*property)))
+                .collect();
+            element.cache = Some(Rc::new(cache));
+            element
+        })
+    }
+}
+
+impl backend::Element for Element {
+    type Condition = Condition;
+
+    fn get_current_property_value(&self, property: Property) -> UiacResult<PropertyValue> {
+        Ok(self.data().property_value(property))
+    }
+
+    fn get_cached_property_value(&self, property: Property) -> UiacResult<PropertyValue> {
+        self.cache
+            .as_ref()
+            .and_then(|cache| cache.get(&property))
+            .cloned()
+            .ok_or_else(|| UiacError::Message(format!("{property} wasn't cached")))
+    }
+
+    fn find_first(&self, scope: TreeScope, condition: &Condition) -> UiacResult<Option<Element>> {
+        Ok(self.with_index(
+            self.tree
+                .scope(self.index, scope)
+                .into_iter()
+                .find(|index| self.tree.matches(*index, condition)),
+        ))
+    }
+
+    fn find_all(&self, scope: TreeScope, condition: &Condition) -> UiacResult<Vec<Element>> {
+        Ok(self
+            .tree
+            .scope(self.index, scope)
+            .into_iter()
+            .filter(|index| self.tree.matches(*index, condition))
+            .map(|index| Element::new(&self.tree, index))
+            .collect())
+    }
+}
+
+/// Walks the view of the tree made up of the elements that match a condition.
+pub struct TreeWalker {
+    condition: Condition,
+}
+
+impl backend::TreeWalker for TreeWalker {
+    type Element = Element;
+    type CacheRequest = CacheRequest;
+
+    fn get_parent_element(&self, element: &Element) -> UiacResult<Option<Element>> {
+        Ok(element.with_index(self.parent(element)))
+    }
+
+    fn get_first_child_element(&self, element: &Element) -> UiacResult<Option<Element>> {
+        Ok(element.with_index(self.first_child(element, Direction::Forward)))
+    }
+
+    fn get_last_child_element(&self, element: &Element) -> UiacResult<Option<Element>> {
+        Ok(element.with_index(self.first_child(element, Direction::Backward)))
+    }
+
+    fn get_next_sibling_element(&self, element: &Element) -> UiacResult<Option<Element>> {
+        Ok(element.with_index(self.next_sibling(element, Direction::Forward)))
+    }
+
+    fn get_previous_sibling_element(&self, element: &Element) -> UiacResult<Option<Element>> {
+        Ok(element.with_index(self.next_sibling(element, Direction::Backward)))
+    }
+
+    fn normalize_element(&self, element: &Element) -> UiacResult<Option<Element>> {
+        Ok(element.with_index(self.normalize(element)))
+    }
+
+    fn get_parent_element_build_cache(
+        &self,
+        element: &Element,
+        cache_request: &CacheRequest,
+    ) -> UiacResult<Option<Element>> {
+        Ok(element.with_index_cached(self.parent(element), cache_request))
+    }
+
+    fn get_first_child_element_build_cache(
+        &self,
+        element: &Element,
+        cache_request: &CacheRequest,
+    ) -> UiacResult<Option<Element>> {
+        Ok(element.with_index_cached(self.first_child(element, Direction::Forward), cache_request))
+    }
+
+    fn get_last_child_element_build_cache(
+        &self,
+        element: &Element,
+        cache_request: &CacheRequest,
+    ) -> UiacResult<Option<Element>> {
+        Ok(element.with_index_cached(
+            self.first_child(element, Direction::Backward),
+            cache_request,
+        ))
+    }
+
+    fn get_next_sibling_element_build_cache(
+        &self,
+        element: &Element,
+        cache_request: &CacheRequest,
+    ) -> UiacResult<Option<Element>> {
+        Ok(element.with_index_cached(
+            self.next_sibling(element, Direction::Forward),
+            cache_request,
+        ))
+    }
+
+    fn get_previous_sibling_element_build_cache(
+        &self,
+        element: &Element,
+        cache_request: &CacheRequest,
+    ) -> UiacResult<Option<Element>> {
+        Ok(element.with_index_cached(
+            self.next_sibling(element, Direction::Backward),
+            cache_request,
+        ))
+    }
+
+    fn normalize_element_build_cache(
+        &self,
+        element: &Element,
+        cache_request: &CacheRequest,
+    ) -> UiacResult<Option<Element>> {
+        Ok(element.with_index_cached(self.normalize(element), cache_request))
+    }
+}
+
+impl TreeWalker {
+    fn parent(&self, element: &Element) -> Option<usize> {
+        element.tree.parent(element.index, &self.condition)
+    }
+
+    fn first_child(&self, element: &Element, direction: Direction) -> Option<usize> {
+        element
+            .tree
+            .first_child(element.index, &self.condition, direction)
+    }
+
+    fn next_sibling(&self, element: &Element, direction: Direction) -> Option<usize> {
+        element
+            .tree
+            .next_sibling(element.index, &self.condition, direction)
+    }
+
+    fn normalize(&self, element: &Element) -> Option<usize> {
+        element.tree.normalize(element.index, &self.condition)
+    }
+}
+
+/// The properties to read along with each element a walker fetches.
+pub struct CacheRequest {
+    properties: Vec<Property>,
+}
+
+/// The memory backend evaluates filters directly.
+pub type Condition = Filter;
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::backend::{Automation as _, Element as _, TreeWalker as _},
+    };
+
+    const FIXTURE: &str = r#"{
+        "name": "Desktop 1",
+        "control_type": "Pane",
+        "children": [
+            {
+                "name": "Taskbar",
+                "control_type": "Pane",
+                "properties": {"ClassName": "Shell_TrayWnd"},
+                "children": [{"name": "Start", "control_type": "Button"}]
+            },
+            {"control_type": "Window"}
+        ]
+    }"#;
+
+    fn names(walker: &TreeWalker, element: &Element) -> UiacResult<Vec<String>> {
+        walker
+            .children(element)
+            .map(|child| child?.get_current_name())
+            .collect()
+    }
+
+    #[test]
+    fn navigation_test() -> UiacResult<()> {
+        let automation = Automation::from_json(FIXTURE)?;
+        let walker = automation.create_tree_walker(&automation.create_true_condition()?)?;
+        let root = automation.get_root_element()?;
+
+        assert_eq!(root.get_current_name()?, "Desktop 1");
+        assert_eq!(names(&walker, &root)?, ["Taskbar", ""]);
+
+        let taskbar = walker.get_first_child_element(&root)?.unwrap();
+        assert_eq!(names(&walker, &taskbar)?, ["Start"]);
+
+        let window = walker.get_next_sibling_element(&taskbar)?.unwrap();
+        assert_eq!(window.get_current_control_type()?, ControlType::Window);
+        assert!(walker.get_first_child_element(&window)?.is_none());
+        assert!(walker.get_next_sibling_element(&window)?.is_none());
+
+        let start = walker.get_first_child_element(&taskbar)?.unwrap();
+        let parent = walker.get_parent_element(&start)?.unwrap();
+        assert_eq!(parent.get_current_name()?, "Taskbar");
+        assert!(walker.get_parent_element(&root)?.is_none());
+
+        let last = walker.get_last_child_element(&root)?.unwrap();
+        assert!(automation.compare_elements(&last, &window)?);
+        let previous = walker.get_previous_sibling_element(&window)?.unwrap();
+        assert!(automation.compare_elements(&previous, &taskbar)?);
+        assert!(walker.get_previous_sibling_element(&taskbar)?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn view_navigation_test() -> UiacResult<()> {
+        let automation = Automation::from_json(FIXTURE)?;
+        let walker = automation.create_tree_walker(&automation.create_property_condition(
+            Property::ControlType,
+            &PropertyValue::Int(ControlType::Button.id() as i32),
+        )?)?;
+        let root = automation.get_root_element()?;
+
+        // The walker climbs past the taskbar, which isn't in its view.
+        let start = walker.get_last_child_element(&root)?.unwrap();
+        assert_eq!(start.get_current_name()?, "Start");
+        let parent = walker.get_parent_element(&start)?.unwrap();
+        assert!(automation.compare_elements(&parent, &root)?);
+
+        let raw_walker = automation.create_tree_walker(&automation.create_true_condition()?)?;
+        let taskbar = raw_walker.get_first_child_element(&root)?.unwrap();
+        let normalized = walker.normalize_element(&taskbar)?.unwrap();
+        assert!(automation.compare_elements(&normalized, &root)?);
+        let normalized = walker.normalize_element(&start)?.unwrap();
+        assert!(automation.compare_elements(&normalized, &start)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn cache_test() -> UiacResult<()> {
+        let automation = Automation::from_json(FIXTURE)?;
+        let walker = automation.create_tree_walker(&automation.create_true_condition()?)?;
+        let cache_request = automation.create_cache_request(&[Property::Name])?;
+        let root = automation.get_root_element()?;
+
+        let taskbar = walker
+            .get_first_child_element_build_cache(&root, &cache_request)?
+            .unwrap();
+        assert_eq!(taskbar.get_cached_name()?, "Taskbar");
+        assert!(taskbar.get_cached_control_type().is_err());
+
+        let start = walker.get_first_child_element(&taskbar)?.unwrap();
+        assert!(start.get_cached_name().is_err());
+        let parent = walker
+            .get_parent_element_build_cache(&start, &cache_request)?
+            .unwrap();
+        assert_eq!(parent.get_cached_name()?, "Taskbar");
+
+        Ok(())
+    }
+
+    #[test]
+    fn properties_test() -> UiacResult<()> {
+        let automation = Automation::from_json(FIXTURE)?;
+        let walker = automation.create_tree_walker(&automation.create_true_condition()?)?;
+        let taskbar = walker
+            .get_first_child_element(&automation.get_root_element()?)?
+            .unwrap();
+
+        assert_eq!(
+            taskbar.get_current_property_value(Property::ClassName)?,
+            PropertyValue::String("Shell_TrayWnd".to_owned())
+        );
+        assert_eq!(
+            taskbar.get_current_property_value(Property::AutomationId)?,
+            PropertyValue::NotSupported
+        );
+        assert_eq!(
+            taskbar.get_current_property_value(Property::ControlType)?,
+            PropertyValue::Int(50033)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn condition_test() -> UiacResult<()> {
+        let automation = Automation::from_json(FIXTURE)?;
+        let root = automation.get_root_element()?;
+
+        let walker = automation.create_tree_walker(
+            &"Name*~=start or ControlType=Window"
+                .parse::<Filter>()?
+                .build(&automation)?,
+        )?;
+        assert_eq!(names(&walker, &root)?, ["Start", ""]);
+
+        let walker = automation.create_tree_walker(
+            &"not ClassName=Shell_TrayWnd"
+                .parse::<Filter>()?
+                .build(&automation)?,
+        )?;
+        assert_eq!(names(&walker, &root)?, ["Start", ""]);
+
+        let walker = automation.create_tree_walker(&automation.create_false_condition()?)?;
+        assert!(walker.get_first_child_element(&root)?.is_none());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,282 @@
+use {
+    crate::{
+        backend::{Automation, Element, TreeWalker},
+        Property, UiacResult,
+    },
+    std::{collections::VecDeque, ops::Deref, rc::Rc},
+};
+
+/// Describes where `element` is in the tree, from the root down, like
+/// `Pane "Desktop 1" > Pane "Taskbar" > Button "Start"`. Only elements in the
+/// walker's view are included, starting with the element itself or its nearest
+/// ancestor in the view.
+pub fn breadcrumb<A: Automation>(
+    automation: &A,
+    walker: &A::TreeWalker,
+    element: &A::Element,
+) -> UiacResult<String> {
+    // Each step up fetches the name and control type along with the element.
+    let cache_request =
+        automation.create_cache_request(&[Property::Name, Property::ControlType])?;
+
+    let mut crumbs = vec![];
+    let mut element_option = walker.normalize_element_build_cache(element, &cache_request)?;
+    while let Some(element) = element_option {
+        let name = element.get_cached_name()?;
+        let control_type = element.get_cached_control_type()?;
+        crumbs.push(if name.is_empty() {
+            control_type.to_string()
+        } else {
+            format!("{control_type} {name:?}")
+        });
+        element_option = walker.get_parent_element_build_cache(&element, &cache_request)?;
+    }
+
+    crumbs.reverse();
+    Ok(crumbs.join(" > "))
+}
+
+/// The walker an iterator steps with. It's borrowed from the caller, or else
+/// made by the iterator for a view and shared with any it makes in turn.
+pub(crate) enum Walker<'a, W> {
+    Borrowed(&'a W),
+    Shared(Rc<W>),
+}
+
+impl<W> Clone for Walker<'_, W> {
+    fn clone(&self) -> Self {
+        match self {
+            Walker::Borrowed(walker) => Walker::Borrowed(walker),
+            Walker::Shared(walker) => Walker::Shared(Rc::clone(walker)),
+        }
+    }
+}
+
+impl<W> Deref for Walker<'_, W> {
+    type Target = W;
+
+    fn deref(&self) -> &W {
+        match self {
+            Walker::Borrowed(walker) => walker,
+            Walker::Shared(walker) => walker,
+        }
+    }
+}
+
+// Each iterator stops after yielding an error, since the walker can't carry on
+// from an element it failed to reach.
+
+/// The children of an element. See `TreeWalker::children`.
+pub struct Children<'a, W: TreeWalker> {
+    walker: Walker<'a, W>,
+    state: State<W::Element>,
+}
+
+enum State<E> {
+    /// Nothing has been yielded yet.
+    Start(E),
+    /// The last element yielded.
+    After(E),
+    Done,
+}
+
+impl<'a, W: TreeWalker> Children<'a, W> {
+    pub(crate) fn new(walker: Walker<'a, W>, element: &W::Element) -> Self {
+        Self {
+            walker,
+            state: State::Start(element.clone()),
+        }
+    }
+}
+
+impl<W: TreeWalker> Iterator for Children<'_, W> {
+    type Item = UiacResult<W::Element>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = match &self.state {
+            State::Start(parent) => self.walker.get_first_child_element(parent),
+            State::After(child) => self.walker.get_next_sibling_element(child),
+            State::Done => return None,
+        };
+        advance(&mut self.state, next)
+    }
+}
+
+/// The siblings after an element. See `TreeWalker::following_siblings`.
+pub struct FollowingSiblings<'a, W: TreeWalker> {
+    walker: Walker<'a, W>,
+    state: State<W::Element>,
+}
+
+impl<'a, W: TreeWalker> FollowingSiblings<'a, W> {
+    pub(crate) fn new(walker: Walker<'a, W>, element: &W::Element) -> Self {
+        Self {
+            walker,
+            state: State::After(element.clone()),
+        }
+    }
+}
+
+impl<W: TreeWalker> Iterator for FollowingSiblings<'_, W> {
+    type Item = UiacResult<W::Element>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = match &self.state {
+            State::Start(sibling) | State::After(sibling) => {
+                self.walker.get_next_sibling_element(sibling)
+            }
+            State::Done => return None,
+        };
+        advance(&mut self.state, next)
+    }
+}
+
+/// The ancestors of an element, nearest first. See `TreeWalker::ancestors`.
+pub struct Ancestors<'a, W: TreeWalker> {
+    walker: Walker<'a, W>,
+    state: State<W::Element>,
+}
+
+impl<'a, W: TreeWalker> Ancestors<'a, W> {
+    pub(crate) fn new(walker: Walker<'a, W>, element: &W::Element) -> Self {
+        Self {
+            walker,
+            state: State::After(element.clone()),
+        }
+    }
+}
+
+impl<W: TreeWalker> Iterator for Ancestors<'_, W> {
+    type Item = UiacResult<W::Element>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = match &self.state {
+            State::Start(element) | State::After(element) => {
+                self.walker.get_parent_element(element)
+            }
+            State::Done => return None,
+        };
+        advance(&mut self.state, next)
+    }
+}
+
+/// Records the outcome of a step in `state` and turns it into the iterator's
+/// next item.
+fn advance<E: Clone>(state: &mut State<E>, next: UiacResult<Option<E>>) -> Option<UiacResult<E>> {
+    match next {
+        Ok(Some(element)) => {
+            *state = State::After(element.clone());
+            Some(Ok(element))
+        }
+        Ok(None) => {
+            *state = State::Done;
+            None
+        }
+        Err(err) => {
+            *state = State::Done;
+            Some(Err(err))
+        }
+    }
+}
+
+/// The descendants of an element in depth-first order. See
+/// `TreeWalker::descendants`.
+pub struct Descendants<'a, W: TreeWalker> {
+    walker: Walker<'a, W>,
+    root: Option<W::Element>,
+    /// The path from the root's child down to the last element yielded.
+    stack: Vec<W::Element>,
+}
+
+impl<'a, W: TreeWalker> Descendants<'a, W> {
+    pub(crate) fn new(walker: Walker<'a, W>, element: &W::Element) -> Self {
+        Self {
+            walker,
+            root: Some(element.clone()),
+            stack: vec![],
+        }
+    }
+
+    fn step(&mut self) -> UiacResult<Option<W::Element>> {
+        if let Some(root) = self.root.take() {
+            return self.walker.get_first_child_element(&root);
+        }
+
+        // Go down if we can, or else along, climbing back up until there's a
+        // sibling to go to.
+        if let Some(last) = self.stack.last() {
+            if let Some(child) = self.walker.get_first_child_element(last)? {
+                return Ok(Some(child));
+            }
+        }
+        while let Some(last) = self.stack.pop() {
+            if let Some(sibling) = self.walker.get_next_sibling_element(&last)? {
+                return Ok(Some(sibling));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<W: TreeWalker> Iterator for Descendants<'_, W> {
+    type Item = UiacResult<W::Element>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.step() {
+            Ok(Some(element)) => {
+                self.stack.push(element.clone());
+                Some(Ok(element))
+            }
+            Ok(None) => None,
+            Err(err) => {
+                self.root = None;
+                self.stack.clear();
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// The descendants of an element level by level. See
+/// `TreeWalker::descendants_breadth_first`.
+pub struct BreadthFirst<'a, W: TreeWalker> {
+    walker: Walker<'a, W>,
+    children: Option<Children<'a, W>>,
+    /// Elements already yielded whose children are still to come.
+    queue: VecDeque<W::Element>,
+}
+
+impl<'a, W: TreeWalker> BreadthFirst<'a, W> {
+    pub(crate) fn new(walker: Walker<'a, W>, element: &W::Element) -> Self {
+        Self {
+            children: Some(Children::new(walker.clone(), element)),
+            walker,
+            queue: VecDeque::new(),
+        }
+    }
+}
+
+impl<W: TreeWalker> Iterator for BreadthFirst<'_, W> {
+    type Item = UiacResult<W::Element>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.children.as_mut()?.next() {
+                Some(Ok(child)) => {
+                    self.queue.push_back(child.clone());
+                    return Some(Ok(child));
+                }
+                Some(Err(err)) => {
+                    self.children = None;
+                    return Some(Err(err));
+                }
+                None => {
+                    self.children = self
+                        .queue
+                        .pop_front()
+                        .map(|parent| Children::new(self.walker.clone(), &parent));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use {
+    crate::{
+        backend::{Element, TreeWalker},
+        snapshot::recorded_properties,
+        traversal::{Event, Order, Traversal, Walk},
+        ControlType, IndexPath, Property, PropertyValue, UiacResult,
+    },
+    serde::Serialize,
+    std::{collections::BTreeMap, io::Write},
+};
+
+/// An element as a line of NDJSON. It has a snapshot node's fields, but in
+/// place of its children, it says where it is in the tree.
+#[derive(Serialize)]
+struct Record {
+    depth: usize,
+    path: Option<IndexPath>,
+    /// `None` for the desktop.
+    parent_path: Option<IndexPath>,
+    name: String,
+    control_type: ControlType,
+    properties: BTreeMap<Property, PropertyValue>,
+}
+
+/// Writes `element` and its subtree as newline-delimited JSON, one object per
+/// element in `order`. Each line is written and flushed as soon as its element
+/// is visited, so consumers can start on the tree before the walk finishes,
+/// and keep what came before if it fails. Paths are null if `path` isn't
+/// given.
+pub(crate) fn write_ndjson<W: TreeWalker>(
+    walk: &Walk<W>,
+    element: &W::Element,
+    path: Option<IndexPath>,
+    order: Order,
+    out: &mut impl Write,
+) -> UiacResult<()> {
+    for event in Traversal::new(walk, element, path, order) {
+        if let Event::Enter(visit) = event? {
+            let record = Record {
+                depth: visit.depth,
+                parent_path: visit.path.as_ref().and_then(IndexPath::parent),
+                name: visit.element.get_current_name()?,
+                control_type: visit.element.get_current_control_type()?,
+                properties: recorded_properties(walk, &visit.element)?,
+                path: visit.path,
+            };
+            serde_json::to_writer(&mut *out, &record)?;
+            writeln!(out)?;
+            out.flush()?;
+        }
+    }
+
+    Ok(())
+}
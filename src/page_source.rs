@@ -0,0 +1,180 @@
+use {
+    crate::{
+        backend::{Element, TreeWalker},
+        property_value::attribute_text,
+        traversal::{Event, Order, Traversal, Walk},
+        ControlType, IndexPath, Property, PropertyValue, UiacResult,
+    },
+    std::io::Write,
+};
+
+/// The attributes WinAppDriver gives every element in its page source, in its
+/// order. They're followed by `x`, `y`, `width` and `height`.
+const ATTRIBUTES: &[Property] = &[
+    Property::AcceleratorKey,
+    Property::AccessKey,
+    Property::AutomationId,
+    Property::ClassName,
+    Property::FrameworkId,
+    Property::HasKeyboardFocus,
+    Property::HelpText,
+    Property::IsContentElement,
+    Property::IsControlElement,
+    Property::IsEnabled,
+    Property::IsKeyboardFocusable,
+    Property::IsOffscreen,
+    Property::IsPassword,
+    Property::IsRequiredForForm,
+    Property::ItemStatus,
+    Property::ItemType,
+    Property::LocalizedControlType,
+    Property::Name,
+    Property::Orientation,
+    Property::ProcessId,
+    Property::RuntimeId,
+];
+
+/// Writes `element` and its subtree as XML in the layout of WinAppDriver's page
+/// source: elements are named after their control type, and have WinAppDriver's
+/// attributes followed by any other properties of the walk. Their position is
+/// given relative to `element`, as WinAppDriver gives it relative to the
+/// application's window. If `path` is given, each element also gets an
+/// `IndexPath` attribute.
+pub(crate) fn write_page_source<W: TreeWalker>(
+    walk: &Walk<W>,
+    element: &W::Element,
+    path: Option<IndexPath>,
+    declaration: bool,
+    out: &mut impl Write,
+) -> UiacResult<()> {
+    if declaration {
+        writeln!(out, r#"<?xml version="1.0" encoding="utf-8"?>"#)?;
+    }
+
+    let mut attributes = ATTRIBUTES.to_vec();
+    attributes.extend(walk.properties.iter().copied().filter(|property| {
+        // Control types are the element names, and bounding rectangles are
+        // split into x, y, width and height.
+        !ATTRIBUTES.contains(property)
+            && !matches!(
+                property,
+                Property::ControlType | Property::BoundingRectangle
+            )
+    }));
+    let (origin_x, origin_y) = match rectangle(element)? {
+        Some([x, y, _, _]) => (x, y),
+        None => (0.0, 0.0),
+    };
+
+    // The names of the elements entered but not left, and whether the last
+    // one's start tag is still open, so it can be closed as an empty element.
+    let mut open: Vec<String> = vec![];
+    let mut start_tag_open = false;
+    for event in Traversal::new(walk, element, path, Order::DepthFirst) {
+        match event? {
+            Event::Enter(visit) => {
+                if start_tag_open {
+                    writeln!(out, ">")?;
+                }
+
+                let name = element_name(visit.element.get_current_control_type()?);
+                write!(out, "{}<{name}", "  ".repeat(open.len()))?;
+                for property in &attributes {
+                    let value = visit.element.get_current_property_value(*property)?;
+                    let text = attribute_text(*property, &value).unwrap_or_default();
+                    write!(out, r#" {property}="{}""#, escape(&text))?;
+                }
+                if let Some([x, y, width, height]) = rectangle(&visit.element)? {
+                    write!(
+                        out,
+                        r#" x="{}" y="{}" width="{}" height="{}""#,
+                        (x - origin_x).round() as i64,
+                        (y - origin_y).round() as i64,
+                        width.round() as i64,
+                        height.round() as i64
+                    )?;
+                }
+                if let Some(path) = &visit.path {
+                    write!(out, r#" IndexPath="{path}""#)?;
+                }
+
+                open.push(name);
+                start_tag_open = true;
+            }
+            Event::Leave => {
+                let name = open.pop().expect("left an element that wasn't entered");
+                if start_tag_open {
+                    writeln!(out, " />")?;
+                    start_tag_open = false;
+                } else {
+                    writeln!(out, "{}</{name}>", "  ".repeat(open.len()))?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// WinAppDriver names elements after their control type. Control types UIA
+/// doesn't know about aren't valid names, so they're called `Custom`.
+fn element_name(control_type: ControlType) -> String {
+    let name = control_type.to_string();
+    if name.chars().all(|c| c.is_ascii_alphanumeric()) {
+        name
+    } else {
+        ControlType::Custom.to_string()
+    }
+}
+
+/// The element's bounding rectangle as left, top, width and height, if it has
+/// one.
+fn rectangle(element: &impl Element) -> UiacResult<Option<[f64; 4]>> {
+    Ok(
+        match element.get_current_property_value(Property::BoundingRectangle)? {
+            PropertyValue::DoubleArray(rect) if rect.len() == 4 => {
+                Some([rect[0], rect[1], rect[2], rect[3]])
+            }
+            _ => None,
+        },
+    )
+}
+
+/// Escapes text for an attribute value. Characters XML doesn't allow at all
+/// are dropped.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\t' => escaped.push_str("&#x9;"),
+            '\n' => escaped.push_str("&#xA;"),
+            '\r' => escaped.push_str("&#xD;"),
+            '\u{0}'..='\u{1f}' | '\u{fffe}' | '\u{ffff}' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_test() {
+        assert_eq!(escape("plain"), "plain");
+        assert_eq!(
+            escape("<a href=\"x\">&</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;"
+        );
+        assert_eq!(
+            escape("line\nbreak\tand\u{7}bell"),
+            "line&#xA;break&#x9;andbell"
+        );
+        assert_eq!(escape("\u{200e}1/22"), "\u{200e}1/22");
+    }
+}
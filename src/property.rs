@@ -0,0 +1,488 @@
+use {
+    crate::{ControlType, PropertyValue, UiacError, UiacResult},
+    serde::{de, Deserialize, Deserializer, Serialize, Serializer},
+    std::{fmt, str::FromStr},
+};
+
+/// The type of value a property is expected to hold.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ValueType {
+    Bool,
+    Int,
+    Double,
+    String,
+    IntArray,
+    DoubleArray,
+    StringArray,
+    /// A point, stored as an array of two doubles.
+    Point,
+    /// A rectangle, stored as an array of four doubles: left, top, width and
+    /// height.
+    Rect,
+    Element,
+    ElementArray,
+}
+
+/// Defines `Property` from a single table of variants, their canonical names,
+/// their value types and their `UIA_PROPERTY_ID` values.
+macro_rules! properties {
+    ($($variant:ident = $id:literal, $name:literal, $value_type:ident;)*) => {
+        #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+        pub enum Property {
+            $($variant,)*
+        }
+
+        impl Property {
+            /// Every property, in order of ID.
+            pub const ALL: &'static [Property] = &[$(Property::$variant,)*];
+
+            /// Maps a `UIA_PROPERTY_ID` value to its property.
+            pub fn new(id: u32) -> Option<Self> {
+                match id {
+                    $($id => Some(Property::$variant),)*
+                    _ => None,
+                }
+            }
+
+            /// The property's `UIA_PROPERTY_ID` value.
+            pub fn id(&self) -> u32 {
+                match self {
+                    $(Property::$variant => $id,)*
+                }
+            }
+
+            /// The property's canonical name. Properties that belong to a
+            /// control pattern are prefixed with the pattern's name, e.g.
+            /// "Value.Value".
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Property::$variant => $name,)*
+                }
+            }
+
+            pub fn value_type(&self) -> ValueType {
+                match self {
+                    $(Property::$variant => ValueType::$value_type,)*
+                }
+            }
+        }
+    };
+}
+
+properties! {
+    RuntimeId = 30000, "RuntimeId", IntArray;
+    BoundingRectangle = 30001, "BoundingRectangle", Rect;
+    ProcessId = 30002, "ProcessId", Int;
+    ControlType = 30003, "ControlType", Int;
+    LocalizedControlType = 30004, "LocalizedControlType", String;
+    Name = 30005, "Name", String;
+    AcceleratorKey = 30006, "AcceleratorKey", String;
+    AccessKey = 30007, "AccessKey", String;
+    HasKeyboardFocus = 30008, "HasKeyboardFocus", Bool;
+    IsKeyboardFocusable = 30009, "IsKeyboardFocusable", Bool;
+    IsEnabled = 30010, "IsEnabled", Bool;
+    AutomationId = 30011, "AutomationId", String;
+    ClassName = 30012, "ClassName", String;
+    HelpText = 30013, "HelpText", String;
+    ClickablePoint = 30014, "ClickablePoint", Point;
+    Culture = 30015, "Culture", Int;
+    IsControlElement = 30016, "IsControlElement", Bool;
+    IsContentElement = 30017, "IsContentElement", Bool;
+    LabeledBy = 30018, "LabeledBy", Element;
+    IsPassword = 30019, "IsPassword", Bool;
+    NativeWindowHandle = 30020, "NativeWindowHandle", Int;
+    ItemType = 30021, "ItemType", String;
+    IsOffscreen = 30022, "IsOffscreen", Bool;
+    Orientation = 30023, "Orientation", Int;
+    FrameworkId = 30024, "FrameworkId", String;
+    IsRequiredForForm = 30025, "IsRequiredForForm", Bool;
+    ItemStatus = 30026, "ItemStatus", String;
+    IsDockPatternAvailable = 30027, "IsDockPatternAvailable", Bool;
+    IsExpandCollapsePatternAvailable = 30028, "IsExpandCollapsePatternAvailable", Bool;
+    IsGridItemPatternAvailable = 30029, "IsGridItemPatternAvailable", Bool;
+    IsGridPatternAvailable = 30030, "IsGridPatternAvailable", Bool;
+    IsInvokePatternAvailable = 30031, "IsInvokePatternAvailable", Bool;
+    IsMultipleViewPatternAvailable = 30032, "IsMultipleViewPatternAvailable", Bool;
+    IsRangeValuePatternAvailable = 30033, "IsRangeValuePatternAvailable", Bool;
+    IsScrollPatternAvailable = 30034, "IsScrollPatternAvailable", Bool;
+    IsScrollItemPatternAvailable = 30035, "IsScrollItemPatternAvailable", Bool;
+    IsSelectionItemPatternAvailable = 30036, "IsSelectionItemPatternAvailable", Bool;
+    IsSelectionPatternAvailable = 30037, "IsSelectionPatternAvailable", Bool;
+    IsTablePatternAvailable = 30038, "IsTablePatternAvailable", Bool;
+    IsTableItemPatternAvailable = 30039, "IsTableItemPatternAvailable", Bool;
+    IsTextPatternAvailable = 30040, "IsTextPatternAvailable", Bool;
+    IsTogglePatternAvailable = 30041, "IsTogglePatternAvailable", Bool;
+    IsTransformPatternAvailable = 30042, "IsTransformPatternAvailable", Bool;
+    IsValuePatternAvailable = 30043, "IsValuePatternAvailable", Bool;
+    IsWindowPatternAvailable = 30044, "IsWindowPatternAvailable", Bool;
+    ValueValue = 30045, "Value.Value", String;
+    ValueIsReadOnly = 30046, "Value.IsReadOnly", Bool;
+    RangeValueValue = 30047, "RangeValue.Value", Double;
+    RangeValueIsReadOnly = 30048, "RangeValue.IsReadOnly", Bool;
+    RangeValueMinimum = 30049, "RangeValue.Minimum", Double;
+    RangeValueMaximum = 30050, "RangeValue.Maximum", Double;
+    RangeValueLargeChange = 30051, "RangeValue.LargeChange", Double;
+    RangeValueSmallChange = 30052, "RangeValue.SmallChange", Double;
+    ScrollHorizontalScrollPercent = 30053, "Scroll.HorizontalScrollPercent", Double;
+    ScrollHorizontalViewSize = 30054, "Scroll.HorizontalViewSize", Double;
+    ScrollVerticalScrollPercent = 30055, "Scroll.VerticalScrollPercent", Double;
+    ScrollVerticalViewSize = 30056, "Scroll.VerticalViewSize", Double;
+    ScrollHorizontallyScrollable = 30057, "Scroll.HorizontallyScrollable", Bool;
+    ScrollVerticallyScrollable = 30058, "Scroll.VerticallyScrollable", Bool;
+    SelectionSelection = 30059, "Selection.Selection", ElementArray;
+    SelectionCanSelectMultiple = 30060, "Selection.CanSelectMultiple", Bool;
+    SelectionIsSelectionRequired = 30061, "Selection.IsSelectionRequired", Bool;
+    GridRowCount = 30062, "Grid.RowCount", Int;
+    GridColumnCount = 30063, "Grid.ColumnCount", Int;
+    GridItemRow = 30064, "GridItem.Row", Int;
+    GridItemColumn = 30065, "GridItem.Column", Int;
+    GridItemRowSpan = 30066, "GridItem.RowSpan", Int;
+    GridItemColumnSpan = 30067, "GridItem.ColumnSpan", Int;
+    GridItemContainingGrid = 30068, "GridItem.ContainingGrid", Element;
+    DockDockPosition = 30069, "Dock.DockPosition", Int;
+    ExpandCollapseExpandCollapseState = 30070, "ExpandCollapse.ExpandCollapseState", Int;
+    MultipleViewCurrentView = 30071, "MultipleView.CurrentView", Int;
+    MultipleViewSupportedViews = 30072, "MultipleView.SupportedViews", IntArray;
+    WindowCanMaximize = 30073, "Window.CanMaximize", Bool;
+    WindowCanMinimize = 30074, "Window.CanMinimize", Bool;
+    WindowWindowVisualState = 30075, "Window.WindowVisualState", Int;
+    WindowWindowInteractionState = 30076, "Window.WindowInteractionState", Int;
+    WindowIsModal = 30077, "Window.IsModal", Bool;
+    WindowIsTopmost = 30078, "Window.IsTopmost", Bool;
+    SelectionItemIsSelected = 30079, "SelectionItem.IsSelected", Bool;
+    SelectionItemSelectionContainer = 30080, "SelectionItem.SelectionContainer", Element;
+    TableRowHeaders = 30081, "Table.RowHeaders", ElementArray;
+    TableColumnHeaders = 30082, "Table.ColumnHeaders", ElementArray;
+    TableRowOrColumnMajor = 30083, "Table.RowOrColumnMajor", Int;
+    TableItemRowHeaderItems = 30084, "TableItem.RowHeaderItems", ElementArray;
+    TableItemColumnHeaderItems = 30085, "TableItem.ColumnHeaderItems", ElementArray;
+    ToggleToggleState = 30086, "Toggle.ToggleState", Int;
+    TransformCanMove = 30087, "Transform.CanMove", Bool;
+    TransformCanResize = 30088, "Transform.CanResize", Bool;
+    TransformCanRotate = 30089, "Transform.CanRotate", Bool;
+    IsLegacyIAccessiblePatternAvailable = 30090, "IsLegacyIAccessiblePatternAvailable", Bool;
+    LegacyIAccessibleChildId = 30091, "LegacyIAccessible.ChildId", Int;
+    LegacyIAccessibleName = 30092, "LegacyIAccessible.Name", String;
+    LegacyIAccessibleValue = 30093, "LegacyIAccessible.Value", String;
+    LegacyIAccessibleDescription = 30094, "LegacyIAccessible.Description", String;
+    LegacyIAccessibleRole = 30095, "LegacyIAccessible.Role", Int;
+    LegacyIAccessibleState = 30096, "LegacyIAccessible.State", Int;
+    LegacyIAccessibleHelp = 30097, "LegacyIAccessible.Help", String;
+    LegacyIAccessibleKeyboardShortcut = 30098, "LegacyIAccessible.KeyboardShortcut", String;
+    LegacyIAccessibleSelection = 30099, "LegacyIAccessible.Selection", ElementArray;
+    LegacyIAccessibleDefaultAction = 30100, "LegacyIAccessible.DefaultAction", String;
+    AriaRole = 30101, "AriaRole", String;
+    AriaProperties = 30102, "AriaProperties", String;
+    IsDataValidForForm = 30103, "IsDataValidForForm", Bool;
+    ControllerFor = 30104, "ControllerFor", ElementArray;
+    DescribedBy = 30105, "DescribedBy", ElementArray;
+    FlowsTo = 30106, "FlowsTo", ElementArray;
+    ProviderDescription = 30107, "ProviderDescription", String;
+    IsItemContainerPatternAvailable = 30108, "IsItemContainerPatternAvailable", Bool;
+    IsVirtualizedItemPatternAvailable = 30109, "IsVirtualizedItemPatternAvailable", Bool;
+    IsSynchronizedInputPatternAvailable = 30110, "IsSynchronizedInputPatternAvailable", Bool;
+    OptimizeForVisualContent = 30111, "OptimizeForVisualContent", Bool;
+    IsObjectModelPatternAvailable = 30112, "IsObjectModelPatternAvailable", Bool;
+    AnnotationAnnotationTypeId = 30113, "Annotation.AnnotationTypeId", Int;
+    AnnotationAnnotationTypeName = 30114, "Annotation.AnnotationTypeName", String;
+    AnnotationAuthor = 30115, "Annotation.Author", String;
+    AnnotationDateTime = 30116, "Annotation.DateTime", String;
+    AnnotationTarget = 30117, "Annotation.Target", Element;
+    IsAnnotationPatternAvailable = 30118, "IsAnnotationPatternAvailable", Bool;
+    IsTextPattern2Available = 30119, "IsTextPattern2Available", Bool;
+    StylesStyleId = 30120, "Styles.StyleId", Int;
+    StylesStyleName = 30121, "Styles.StyleName", String;
+    StylesFillColor = 30122, "Styles.FillColor", Int;
+    StylesFillPatternStyle = 30123, "Styles.FillPatternStyle", String;
+    StylesShape = 30124, "Styles.Shape", String;
+    StylesFillPatternColor = 30125, "Styles.FillPatternColor", Int;
+    StylesExtendedProperties = 30126, "Styles.ExtendedProperties", String;
+    IsStylesPatternAvailable = 30127, "IsStylesPatternAvailable", Bool;
+    IsSpreadsheetPatternAvailable = 30128, "IsSpreadsheetPatternAvailable", Bool;
+    SpreadsheetItemFormula = 30129, "SpreadsheetItem.Formula", String;
+    SpreadsheetItemAnnotationObjects = 30130, "SpreadsheetItem.AnnotationObjects", ElementArray;
+    SpreadsheetItemAnnotationTypes = 30131, "SpreadsheetItem.AnnotationTypes", IntArray;
+    IsSpreadsheetItemPatternAvailable = 30132, "IsSpreadsheetItemPatternAvailable", Bool;
+    Transform2CanZoom = 30133, "Transform2.CanZoom", Bool;
+    IsTransformPattern2Available = 30134, "IsTransformPattern2Available", Bool;
+    LiveSetting = 30135, "LiveSetting", Int;
+    IsTextChildPatternAvailable = 30136, "IsTextChildPatternAvailable", Bool;
+    IsDragPatternAvailable = 30137, "IsDragPatternAvailable", Bool;
+    DragIsGrabbed = 30138, "Drag.IsGrabbed", Bool;
+    DragDropEffect = 30139, "Drag.DropEffect", String;
+    DragDropEffects = 30140, "Drag.DropEffects", StringArray;
+    IsDropTargetPatternAvailable = 30141, "IsDropTargetPatternAvailable", Bool;
+    DropTargetDropTargetEffect = 30142, "DropTarget.DropTargetEffect", String;
+    DropTargetDropTargetEffects = 30143, "DropTarget.DropTargetEffects", StringArray;
+    DragGrabbedItems = 30144, "Drag.GrabbedItems", ElementArray;
+    Transform2ZoomLevel = 30145, "Transform2.ZoomLevel", Double;
+    Transform2ZoomMinimum = 30146, "Transform2.ZoomMinimum", Double;
+    Transform2ZoomMaximum = 30147, "Transform2.ZoomMaximum", Double;
+    FlowsFrom = 30148, "FlowsFrom", ElementArray;
+    IsTextEditPatternAvailable = 30149, "IsTextEditPatternAvailable", Bool;
+    IsPeripheral = 30150, "IsPeripheral", Bool;
+    IsCustomNavigationPatternAvailable = 30151, "IsCustomNavigationPatternAvailable", Bool;
+    PositionInSet = 30152, "PositionInSet", Int;
+    SizeOfSet = 30153, "SizeOfSet", Int;
+    Level = 30154, "Level", Int;
+    AnnotationTypes = 30155, "AnnotationTypes", IntArray;
+    AnnotationObjects = 30156, "AnnotationObjects", ElementArray;
+    LandmarkType = 30157, "LandmarkType", Int;
+    LocalizedLandmarkType = 30158, "LocalizedLandmarkType", String;
+    FullDescription = 30159, "FullDescription", String;
+    FillColor = 30160, "FillColor", Int;
+    OutlineColor = 30161, "OutlineColor", IntArray;
+    FillType = 30162, "FillType", Int;
+    VisualEffects = 30163, "VisualEffects", Int;
+    OutlineThickness = 30164, "OutlineThickness", DoubleArray;
+    CenterPoint = 30165, "CenterPoint", Point;
+    Rotation = 30166, "Rotation", Double;
+    Size = 30167, "Size", DoubleArray;
+    IsSelectionPattern2Available = 30168, "IsSelectionPattern2Available", Bool;
+    Selection2FirstSelectedItem = 30169, "Selection2.FirstSelectedItem", Element;
+    Selection2LastSelectedItem = 30170, "Selection2.LastSelectedItem", Element;
+    Selection2CurrentSelectedItem = 30171, "Selection2.CurrentSelectedItem", Element;
+    Selection2ItemCount = 30172, "Selection2.ItemCount", Int;
+    HeadingLevel = 30173, "HeadingLevel", Int;
+    IsDialog = 30174, "IsDialog", Bool;
+}
+
+impl fmt::Display for Property {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Property {
+    type Err = UiacError;
+
+    /// Parses a property's canonical name, ignoring case. The dot in a
+    /// pattern property's name is optional, so "ValueValue" is the same as
+    /// "Value.Value".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let undotted = |name: &str| name.replace('.', "");
+        Property::ALL
+            .iter()
+            .find(|property| {
+                property.name().eq_ignore_ascii_case(s)
+                    || undotted(property.name()).eq_ignore_ascii_case(s)
+            })
+            .copied()
+            .ok_or_else(|| UiacError::InvalidProperty(s.to_owned()))
+    }
+}
+
+impl Property {
+    /// Parses a value of this property's type from a string, e.g. "true" for
+    /// `IsEnabled` or "Button" for `ControlType`. Only properties with scalar
+    /// values can be parsed.
+    pub fn parse_value(&self, s: &str) -> UiacResult<PropertyValue> {
+        let invalid = || UiacError::Message(format!("invalid value {s:?} for property {self}"));
+
+        Ok(match self.value_type() {
+            _ if *self == Property::ControlType => {
+                PropertyValue::Int(s.parse::<ControlType>()?.id() as i32)
+            }
+            ValueType::Bool => PropertyValue::Bool(s.parse().map_err(|_| invalid())?),
+            ValueType::Int => PropertyValue::Int(parse_int(s).ok_or_else(invalid)?),
+            ValueType::Double => PropertyValue::Double(s.parse().map_err(|_| invalid())?),
+            ValueType::String => PropertyValue::String(s.to_owned()),
+            _ => {
+                return Err(UiacError::Message(format!(
+                    "property {self} can't be parsed from a string"
+                )))
+            }
+        })
+    }
+}
+
+/// Parses an integer in decimal, or in hex with a "0x" prefix as is common for
+/// window handles.
+fn parse_int(s: &str) -> Option<i32> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok().map(|i| i as i32),
+        None => s.parse().ok(),
+    }
+}
+
+/// A property paired with the value it should have, parsed from
+/// `Property=value`. The value may be wrapped in double quotes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PropertyMatch {
+    pub property: Property,
+    pub value: PropertyValue,
+}
+
+impl PropertyMatch {
+    pub fn new(property: Property, value: PropertyValue) -> Self {
+        Self { property, value }
+    }
+}
+
+impl fmt::Display for PropertyMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} = {}", self.property, self.value)
+    }
+}
+
+impl FromStr for PropertyMatch {
+    type Err = UiacError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (property, value) = s
+            .split_once('=')
+            .ok_or_else(|| UiacError::Message(format!("expected Property=value, got {s:?}")))?;
+        let property: Property = property.trim().parse()?;
+
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .unwrap_or(value);
+
+        Ok(Self::new(property, property.parse_value(value)?))
+    }
+}
+
+/// A list of properties, parsed from either a comma-separated list of names or
+/// "all".
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PropertyList(pub Vec<Property>);
+
+impl FromStr for PropertyList {
+    type Err = UiacError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("all") {
+            return Ok(PropertyList(Property::ALL.to_vec()));
+        }
+
+        Ok(PropertyList(
+            s.split(',')
+                .map(|name| name.trim().parse())
+                .collect::<Result<_, _>>()?,
+        ))
+    }
+}
+
+impl Serialize for Property {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Property {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn property_new_test() {
+        for property in Property::ALL {
+            assert_eq!(Property::new(property.id()), Some(*property));
+        }
+
+        assert_eq!(Property::new(30005), Some(Property::Name));
+        assert_eq!(Property::new(30045), Some(Property::ValueValue));
+        assert_eq!(Property::new(29999), None);
+    }
+
+    #[test]
+    fn property_from_str_test() -> Result<(), UiacError> {
+        for property in Property::ALL {
+            assert_eq!(property.to_string().parse::<Property>()?, *property);
+        }
+
+        assert_eq!("AutomationId".parse::<Property>()?, Property::AutomationId);
+        assert_eq!("isenabled".parse::<Property>()?, Property::IsEnabled);
+        assert_eq!("Value.Value".parse::<Property>()?, Property::ValueValue);
+        assert_eq!("ValueValue".parse::<Property>()?, Property::ValueValue);
+
+        assert!("AutomationID2".parse::<Property>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn property_list_from_str_test() -> Result<(), UiacError> {
+        assert_eq!(
+            "Name, AutomationId,IsEnabled".parse::<PropertyList>()?,
+            PropertyList(vec![
+                Property::Name,
+                Property::AutomationId,
+                Property::IsEnabled
+            ])
+        );
+        assert_eq!("all".parse::<PropertyList>()?.0, Property::ALL);
+
+        assert!("Name,,AutomationId".parse::<PropertyList>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn property_match_from_str_test() -> Result<(), UiacError> {
+        let matches = [
+            (
+                "Name=Notepad",
+                Property::Name,
+                PropertyValue::String("Notepad".to_owned()),
+            ),
+            (
+                r#"Name = "Untitled - Notepad""#,
+                Property::Name,
+                PropertyValue::String("Untitled - Notepad".to_owned()),
+            ),
+            (
+                "IsEnabled=false",
+                Property::IsEnabled,
+                PropertyValue::Bool(false),
+            ),
+            (
+                "ProcessId=1234",
+                Property::ProcessId,
+                PropertyValue::Int(1234),
+            ),
+            (
+                "NativeWindowHandle=0x1A2B",
+                Property::NativeWindowHandle,
+                PropertyValue::Int(0x1A2B),
+            ),
+            (
+                "ControlType=Button",
+                Property::ControlType,
+                PropertyValue::Int(50000),
+            ),
+        ];
+
+        for (s, property, value) in matches {
+            assert_eq!(
+                s.parse::<PropertyMatch>()?,
+                PropertyMatch::new(property, value)
+            );
+        }
+
+        assert!("Name".parse::<PropertyMatch>().is_err());
+        assert!("IsEnabled=maybe".parse::<PropertyMatch>().is_err());
+        assert!("BoundingRectangle=0".parse::<PropertyMatch>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn property_value_type_test() {
+        assert_eq!(Property::Name.value_type(), ValueType::String);
+        assert_eq!(Property::IsEnabled.value_type(), ValueType::Bool);
+        assert_eq!(Property::BoundingRectangle.value_type(), ValueType::Rect);
+        assert_eq!(Property::ClickablePoint.value_type(), ValueType::Point);
+        assert_eq!(Property::RuntimeId.value_type(), ValueType::IntArray);
+        assert_eq!(Property::LabeledBy.value_type(), ValueType::Element);
+    }
+}
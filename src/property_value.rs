@@ -0,0 +1,295 @@
+use {
+    crate::{ControlType, Property, UiacError, UiacResult},
+    serde::{Deserialize, Serialize},
+    serde_json::{json, Value},
+    std::fmt,
+};
+
+/// The value of a UIA property, decoded from whatever representation the
+/// backend uses.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(into = "Value", try_from = "Value")]
+pub enum PropertyValue {
+    Empty,
+    Bool(bool),
+    Int(i32),
+    Double(f64),
+    String(String),
+    IntArray(Vec<i32>),
+    DoubleArray(Vec<f64>),
+    StringArray(Vec<String>),
+    /// A reference to another element, such as the target of `LabeledBy`.
+    Element(ElementRef),
+    ElementArray(Vec<ElementRef>),
+    /// UIA's reserved value for a property the element doesn't support.
+    NotSupported,
+    /// UIA's reserved value for a text attribute that varies across a range.
+    Mixed,
+}
+
+/// Identifies an element referred to by a property value.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ElementRef {
+    pub name: String,
+    pub control_type: ControlType,
+}
+
+impl PropertyValue {
+    pub fn into_string(self) -> UiacResult<String> {
+        match self {
+            PropertyValue::String(s) => Ok(s),
+            _ => Err(UiacError::InvalidVariantType),
+        }
+    }
+
+    pub fn as_control_type(&self) -> UiacResult<ControlType> {
+        match self {
+            PropertyValue::Int(id) => Ok(ControlType::new(*id as u32)),
+            _ => Err(UiacError::InvalidVariantType),
+        }
+    }
+}
+
+/// The text that selectors and XPath compare a property's value against.
+/// Control types are compared by name. Values that have no sensible text, like
+/// arrays or the reserved values, give `None`.
+pub(crate) fn value_text(property: Property, value: &PropertyValue) -> Option<String> {
+    match (property, value) {
+        (Property::ControlType, PropertyValue::Int(id)) => {
+            Some(ControlType::new(*id as u32).to_string())
+        }
+        (_, PropertyValue::String(s)) => Some(s.clone()),
+        (_, PropertyValue::Bool(_) | PropertyValue::Int(_) | PropertyValue::Double(_)) => {
+            Some(value.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// The text of a property's attribute in WinAppDriver's page source, which
+/// XPath also compares against. Booleans read as "True" or "False", runtime IDs
+/// as numbers joined by dots and orientations by name.
+pub(crate) fn attribute_text(property: Property, value: &PropertyValue) -> Option<String> {
+    match (property, value) {
+        (_, PropertyValue::Bool(true)) => Some("True".to_owned()),
+        (_, PropertyValue::Bool(false)) => Some("False".to_owned()),
+        (Property::RuntimeId, PropertyValue::IntArray(ids)) => {
+            let ids: Vec<String> = ids.iter().map(ToString::to_string).collect();
+            Some(ids.join("."))
+        }
+        (Property::Orientation, PropertyValue::Int(0)) => Some("None".to_owned()),
+        (Property::Orientation, PropertyValue::Int(1)) => Some("Horizontal".to_owned()),
+        (Property::Orientation, PropertyValue::Int(2)) => Some("Vertical".to_owned()),
+        _ => value_text(property, value),
+    }
+}
+
+impl fmt::Display for ElementRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {:?}", self.control_type, self.name)
+    }
+}
+
+impl fmt::Display for PropertyValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PropertyValue::Empty => write!(f, "[empty]"),
+            PropertyValue::Bool(b) => write!(f, "{b}"),
+            PropertyValue::Int(i) => write!(f, "{i}"),
+            PropertyValue::Double(d) => write!(f, "{d}"),
+            PropertyValue::String(s) => write!(f, "{s:?}"),
+            PropertyValue::IntArray(a) => write!(f, "{a:?}"),
+            PropertyValue::DoubleArray(a) => write!(f, "{a:?}"),
+            PropertyValue::StringArray(a) => write!(f, "{a:?}"),
+            PropertyValue::Element(element) => write!(f, "{element}"),
+            PropertyValue::ElementArray(elements) => {
+                let elements: Vec<String> = elements.iter().map(ToString::to_string).collect();
+                write!(f, "[{}]", elements.join(", "))
+            }
+            PropertyValue::NotSupported => write!(f, "[not supported]"),
+            PropertyValue::Mixed => write!(f, "[mixed]"),
+        }
+    }
+}
+
+impl From<PropertyValue> for Value {
+    fn from(value: PropertyValue) -> Self {
+        match value {
+            PropertyValue::Empty => Value::Null,
+            PropertyValue::Bool(b) => b.into(),
+            PropertyValue::Int(i) => i.into(),
+            PropertyValue::Double(d) => d.into(),
+            PropertyValue::String(s) => s.into(),
+            PropertyValue::IntArray(a) => a.into(),
+            PropertyValue::DoubleArray(a) => a.into(),
+            PropertyValue::StringArray(a) => a.into(),
+            PropertyValue::Element(element) => element_ref_to_json(element),
+            PropertyValue::ElementArray(elements) => {
+                elements.into_iter().map(element_ref_to_json).collect()
+            }
+            PropertyValue::NotSupported => json!({"reserved": "NotSupported"}),
+            PropertyValue::Mixed => json!({"reserved": "Mixed"}),
+        }
+    }
+}
+
+fn element_ref_to_json(element: ElementRef) -> Value {
+    json!({"name": element.name, "control_type": element.control_type})
+}
+
+impl TryFrom<Value> for PropertyValue {
+    type Error = UiacError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let invalid = || UiacError::Message(format!("invalid property value {value}"));
+
+        Ok(match &value {
+            Value::Null => PropertyValue::Empty,
+            Value::Bool(b) => PropertyValue::Bool(*b),
+            Value::Number(n) => match n.as_i64().and_then(|i| i.try_into().ok()) {
+                Some(i) => PropertyValue::Int(i),
+                None => PropertyValue::Double(n.as_f64().ok_or_else(invalid)?),
+            },
+            Value::String(s) => PropertyValue::String(s.clone()),
+            Value::Array(a) => match a.first() {
+                None => PropertyValue::IntArray(vec![]),
+                Some(Value::String(_)) => PropertyValue::StringArray(
+                    a.iter()
+                        .map(|v| v.as_str().map(str::to_owned).ok_or_else(invalid))
+                        .collect::<Result<_, _>>()?,
+                ),
+                Some(Value::Object(_)) => {
+                    PropertyValue::ElementArray(serde_json::from_value(value.clone())?)
+                }
+                Some(_) => {
+                    let ints: Option<Vec<i32>> = a
+                        .iter()
+                        .map(|v| v.as_i64().and_then(|i| i.try_into().ok()))
+                        .collect();
+                    match ints {
+                        Some(ints) => PropertyValue::IntArray(ints),
+                        None => PropertyValue::DoubleArray(
+                            a.iter()
+                                .map(|v| v.as_f64().ok_or_else(invalid))
+                                .collect::<Result<_, _>>()?,
+                        ),
+                    }
+                }
+            },
+            Value::Object(o) => match o.get("reserved").and_then(Value::as_str) {
+                Some("NotSupported") => PropertyValue::NotSupported,
+                Some("Mixed") => PropertyValue::Mixed,
+                Some(_) => return Err(invalid()),
+                None => PropertyValue::Element(serde_json::from_value(value.clone())?),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn property_value_json_round_trip_test() -> Result<(), UiacError> {
+        let values = [
+            (PropertyValue::Empty, "null"),
+            (PropertyValue::Bool(true), "true"),
+            (PropertyValue::Int(-5), "-5"),
+            (PropertyValue::Double(1.5), "1.5"),
+            (PropertyValue::Double(2.0), "2.0"),
+            (PropertyValue::String("OK".to_owned()), r#""OK""#),
+            (PropertyValue::IntArray(vec![42, 1, 7]), "[42,1,7]"),
+            (
+                PropertyValue::DoubleArray(vec![0.0, 0.0, 1920.0, 40.5]),
+                "[0.0,0.0,1920.0,40.5]",
+            ),
+            (
+                PropertyValue::StringArray(vec!["copy".to_owned(), "move".to_owned()]),
+                r#"["copy","move"]"#,
+            ),
+            (
+                PropertyValue::Element(ElementRef {
+                    name: "User name".to_owned(),
+                    control_type: ControlType::Text,
+                }),
+                r#"{"control_type":"Text","name":"User name"}"#,
+            ),
+            (
+                PropertyValue::ElementArray(vec![ElementRef {
+                    name: "Column 1".to_owned(),
+                    control_type: ControlType::HeaderItem,
+                }]),
+                r#"[{"control_type":"HeaderItem","name":"Column 1"}]"#,
+            ),
+            (
+                PropertyValue::NotSupported,
+                r#"{"reserved":"NotSupported"}"#,
+            ),
+            (PropertyValue::Mixed, r#"{"reserved":"Mixed"}"#),
+        ];
+
+        for (value, json) in values {
+            assert_eq!(serde_json::to_string(&value)?, json);
+            assert_eq!(serde_json::from_str::<PropertyValue>(json)?, value);
+        }
+
+        assert!(serde_json::from_str::<PropertyValue>(r#"{"reserved":"Other"}"#).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn property_value_display_test() {
+        let values = [
+            (PropertyValue::Empty, "[empty]"),
+            (PropertyValue::Bool(false), "false"),
+            (PropertyValue::String("a\nb".to_owned()), r#""a\nb""#),
+            (PropertyValue::DoubleArray(vec![1.0, 2.5]), "[1.0, 2.5]"),
+            (
+                PropertyValue::Element(ElementRef {
+                    name: "OK".to_owned(),
+                    control_type: ControlType::Button,
+                }),
+                r#"Button "OK""#,
+            ),
+            (PropertyValue::NotSupported, "[not supported]"),
+        ];
+
+        for (value, string) in values {
+            assert_eq!(value.to_string(), string);
+        }
+    }
+
+    #[test]
+    fn attribute_text_test() {
+        let values = [
+            (Property::IsEnabled, PropertyValue::Bool(true), Some("True")),
+            (
+                Property::Name,
+                PropertyValue::String("OK".to_owned()),
+                Some("OK"),
+            ),
+            (
+                Property::ControlType,
+                PropertyValue::Int(ControlType::Button.id() as i32),
+                Some("Button"),
+            ),
+            (
+                Property::RuntimeId,
+                PropertyValue::IntArray(vec![42, 65552]),
+                Some("42.65552"),
+            ),
+            (
+                Property::Orientation,
+                PropertyValue::Int(1),
+                Some("Horizontal"),
+            ),
+            (Property::Name, PropertyValue::NotSupported, None),
+        ];
+
+        for (property, value, text) in values {
+            assert_eq!(attribute_text(property, &value).as_deref(), text);
+        }
+    }
+}
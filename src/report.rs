@@ -0,0 +1,54 @@
+use {
+    crate::{
+        snapshot::{write_json, Snapshot},
+        UiacResult,
+    },
+    std::io::Write,
+};
+
+/// The page the report is built from, with a placeholder for the snapshot.
+const TEMPLATE: &str = include_str!("report.html");
+const PLACEHOLDER: &str = "/* snapshot */";
+
+/// Writes `snapshot` as a self-contained HTML page for browsing the tree, with
+/// the snapshot embedded as JSON and the styles and scripts inline, so it
+/// opens offline without uiac.
+pub(crate) fn write_report(snapshot: &Snapshot, out: &mut impl Write) -> UiacResult<()> {
+    let (head, tail) = TEMPLATE
+        .split_once(PLACEHOLDER)
+        .expect("the report template has a placeholder for the snapshot");
+
+    let mut json = vec![];
+    write_json(snapshot, false, &mut json)?;
+    let json = String::from_utf8(json).expect("serde_json writes UTF-8");
+
+    out.write_all(head.as_bytes())?;
+    out.write_all(escape_script(&json).as_bytes())?;
+    out.write_all(tail.as_bytes())?;
+
+    Ok(())
+}
+
+/// Escapes JSON to sit in a script element. `<` only appears in JSON inside
+/// strings, where `<` means the same, so no name can close the element.
+fn escape_script(json: &str) -> String {
+    json.replace('<', "\\u003c")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_script_test() -> UiacResult<()> {
+        let json = serde_json::to_string("</script><!-- a < b")?;
+        let escaped = escape_script(&json);
+        assert!(!escaped.contains('<'));
+        assert_eq!(
+            serde_json::from_str::<String>(&escaped)?,
+            "</script><!-- a < b"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,95 @@
+use {
+    crate::{
+        backend::{Automation, Element},
+        dot::{write_dot, DotOptions},
+        dump::format_value,
+        ndjson::write_ndjson,
+        page_source::write_page_source,
+        report::write_report,
+        snapshot::{capture_tree, Snapshot},
+        table::{write_table, Delimiter},
+        traversal::{Order, Walk},
+        Filter, Format, IndexPath, Property, PropertyValue, UiacResult,
+    },
+    std::io::Write,
+};
+
+#[derive(Debug)]
+pub struct ResolveOptions {
+    pub format: Format,
+    pub path: IndexPath,
+    /// The properties to print, in order. Those the element doesn't support
+    /// are left out.
+    pub properties: Vec<Property>,
+}
+
+impl Default for ResolveOptions {
+    fn default() -> Self {
+        Self {
+            format: Format::default(),
+            path: IndexPath::default(),
+            properties: Property::ALL.to_vec(),
+        }
+    }
+}
+
+/// Finds the element at an index path and prints its properties, one per line.
+pub fn resolve<A: Automation>(
+    automation: &A,
+    options: &ResolveOptions,
+    out: &mut impl Write,
+) -> UiacResult<()> {
+    let walker = automation.create_tree_walker(&automation.get_raw_view_condition()?)?;
+    let element = options
+        .path
+        .resolve(&walker, &automation.get_root_element()?)?;
+
+    let mut properties = vec![];
+    for property in &options.properties {
+        let value = element.get_current_property_value(*property)?;
+        if value != PropertyValue::NotSupported {
+            properties.push((*property, value));
+        }
+    }
+
+    let supported: Vec<Property> = properties.iter().map(|(property, _)| *property).collect();
+    let walk = Walk {
+        walker: &walker,
+        properties: &supported,
+        max_depth: Some(0),
+        filter: &Filter::True,
+    };
+    let path = Some(options.path.clone());
+
+    match options.format {
+        Format::Text => {
+            writeln!(out, "{}", options.path)?;
+            for (property, value) in &properties {
+                writeln!(out, "  {property} = {}", format_value(*property, value))?;
+            }
+        }
+        Format::Json => {
+            let node = capture_tree(&walk, &element, path)?;
+            serde_json::to_writer_pretty(&mut *out, &node)?;
+            writeln!(out)?;
+        }
+        Format::Xml => write_page_source(&walk, &element, path, false, out)?,
+        Format::Dot => write_dot(&walk, &element, path, DotOptions::default(), out)?,
+        Format::Html => {
+            let snapshot = Snapshot::capture(&walk, &element, path, options.path.to_string())?;
+            write_report(&snapshot, out)?;
+        }
+        Format::Csv => write_table(
+            &walk,
+            &element,
+            path,
+            Order::default(),
+            Delimiter::Comma,
+            out,
+        )?,
+        Format::Tsv => write_table(&walk, &element, path, Order::default(), Delimiter::Tab, out)?,
+        Format::Ndjson => write_ndjson(&walk, &element, path, Order::default(), out)?,
+    }
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use {
+    crate::{
+        backend::{Automation, Element, TreeWalker},
+        Filter, IndexPath, UiacError, UiacResult,
+    },
+    std::fmt,
+};
+
+/// Where a walk of the UIA tree starts.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Root {
+    /// The desktop, which is the root of the whole tree.
+    #[default]
+    Desktop,
+    /// The element that has keyboard focus.
+    Focused,
+    /// The first top-level window that matches the filter.
+    Window(Filter),
+    /// The first element in the tree, in depth-first order, that matches the
+    /// filter.
+    Query(Filter),
+    /// The element at an index path.
+    Path(IndexPath),
+}
+
+impl Root {
+    pub fn find<A: Automation>(
+        &self,
+        automation: &A,
+        walker: &A::TreeWalker,
+    ) -> UiacResult<A::Element> {
+        let desktop = automation.get_root_element()?;
+
+        let found = match self {
+            Root::Desktop => Some(desktop),
+            Root::Focused => Some(automation.get_focused_element()?),
+            Root::Window(filter) => find_match(walker.children(&desktop), filter)?,
+            Root::Path(path) => Some(path.resolve(walker, &desktop)?),
+            Root::Query(filter) => {
+                if filter.matches(&desktop)? {
+                    Some(desktop)
+                } else {
+                    find_match(walker.descendants(&desktop), filter)?
+                }
+            }
+        };
+
+        found.ok_or_else(|| UiacError::Message(format!("couldn't find the {self}")))
+    }
+}
+
+impl fmt::Display for Root {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Root::Desktop => write!(f, "desktop"),
+            Root::Focused => write!(f, "focused element"),
+            Root::Window(filter) => write!(f, "window matching {filter}"),
+            Root::Query(filter) => write!(f, "element matching {filter}"),
+            Root::Path(path) => write!(f, "element at {path}"),
+        }
+    }
+}
+
+fn find_match<E: Element>(
+    elements: impl Iterator<Item = UiacResult<E>>,
+    filter: &Filter,
+) -> UiacResult<Option<E>> {
+    for element in elements {
+        let element = element?;
+        if filter.matches(&element)? {
+            return Ok(Some(element));
+        }
+    }
+
+    Ok(None)
+}
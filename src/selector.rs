@@ -0,0 +1,640 @@
+use {
+    crate::{
+        backend::{Element, TreeWalker},
+        condition::Quoted,
+        matcher::{parse_operator, Matcher, OPERATOR_CHARS},
+        property_value::value_text,
+        ControlType, Property, PropertyValue, UiacError, UiacResult,
+    },
+    std::{collections::BTreeMap, fmt, str::FromStr},
+};
+
+/// A CSS-like selector such as
+/// `Window[Name="Notepad"] > Pane Edit[AutomationId="15"]:nth(2)`.
+///
+/// Each step names a control type (or `*` for any) followed by any number of
+/// attribute matches and pseudo-classes. Steps are separated by `>` to match
+/// children of the previous step, or by whitespace to match any descendants.
+/// A selector is evaluated below a root element, so the first step matches
+/// the root's descendants, or only its children if the selector starts with
+/// `>`.
+///
+/// Attributes are UIA properties, matched with the operators of filters: `=`
+/// (equals), `^=` (starts with), `$=` (ends with), `*=` (contains), `?=`
+/// (matches a glob) or `/=` (matches a regular expression), with `~` before
+/// the `=` to ignore case and then `_` to normalize whitespace. An attribute
+/// with no operator, like `[AutomationId]`, matches elements that have a
+/// non-empty value for the property. The pseudo-classes
+/// `:first`, `:last` and `:nth(n)` pick among the elements a step matched, and
+/// `:nth-child(n)` matches elements by their position among their siblings.
+/// Positions count from 1.
+#[derive(Clone, Debug)]
+pub struct Selector {
+    pub steps: Vec<Step>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Step {
+    pub combinator: Combinator,
+    /// The control type to match, or `None` for any.
+    pub control_type: Option<ControlType>,
+    pub attributes: Vec<Attribute>,
+    pub pseudo_classes: Vec<PseudoClass>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Combinator {
+    Child,
+    Descendant,
+}
+
+#[derive(Clone, Debug)]
+pub struct Attribute {
+    pub property: Property,
+    /// How to match the property's text, or `None` to only require a value.
+    pub matcher: Option<Matcher>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PseudoClass {
+    First,
+    Last,
+    Nth(usize),
+    NthChild(usize),
+}
+
+impl Selector {
+    /// Finds every element below `root` that the selector matches, in
+    /// depth-first order.
+    pub fn find_all<W: TreeWalker>(
+        &self,
+        walker: &W,
+        root: &W::Element,
+    ) -> UiacResult<Vec<W::Element>> {
+        Ok(self.find_all_paths(walker, root)?.into_values().collect())
+    }
+
+    /// Like `find_all`, but keys each match by its index path below the root,
+    /// which identifies it and sorts it in depth-first order.
+    pub(crate) fn find_all_paths<W: TreeWalker>(
+        &self,
+        walker: &W,
+        root: &W::Element,
+    ) -> UiacResult<BTreeMap<Vec<usize>, W::Element>> {
+        let mut contexts = BTreeMap::from([(vec![], root.clone())]);
+
+        for step in &self.steps {
+            let mut matches = BTreeMap::new();
+            let mut searched: Option<&Vec<usize>> = None;
+
+            for (path, context) in &contexts {
+                match step.combinator {
+                    Combinator::Child => {
+                        step.collect_children(walker, context, path, false, &mut matches)?
+                    }
+                    Combinator::Descendant => {
+                        // A context inside another context's subtree has
+                        // already been searched.
+                        if searched.is_some_and(|searched| path.starts_with(searched)) {
+                            continue;
+                        }
+                        step.collect_children(walker, context, path, true, &mut matches)?;
+                        searched = Some(path);
+                    }
+                }
+            }
+
+            contexts = step.pick(matches);
+            if contexts.is_empty() {
+                break;
+            }
+        }
+
+        Ok(contexts)
+    }
+
+    pub fn find_first<W: TreeWalker>(
+        &self,
+        walker: &W,
+        root: &W::Element,
+    ) -> UiacResult<Option<W::Element>> {
+        Ok(self.find_all(walker, root)?.into_iter().next())
+    }
+}
+
+impl Step {
+    fn collect_children<W: TreeWalker>(
+        &self,
+        walker: &W,
+        element: &W::Element,
+        path: &[usize],
+        recursive: bool,
+        matches: &mut BTreeMap<Vec<usize>, W::Element>,
+    ) -> UiacResult<()> {
+        // The children being gone through at each level below `element`, the
+        // deepest last. `path` leads to the last child reached.
+        let mut path = path.to_vec();
+        let mut levels = vec![walker.children(element).enumerate()];
+        while let Some(children) = levels.last_mut() {
+            let Some((index, child)) = children.next() else {
+                levels.pop();
+                path.pop();
+                continue;
+            };
+            let child = child?;
+            path.push(index);
+
+            if self.matches(&child, index + 1)? {
+                matches.insert(path.clone(), child.clone());
+            }
+
+            if recursive {
+                levels.push(walker.children(&child).enumerate());
+            } else {
+                path.pop();
+            }
+        }
+
+        Ok(())
+    }
+
+    fn matches(&self, element: &impl Element, position: usize) -> UiacResult<bool> {
+        if let Some(control_type) = self.control_type {
+            if element.get_current_control_type()? != control_type {
+                return Ok(false);
+            }
+        }
+
+        for pseudo_class in &self.pseudo_classes {
+            if let PseudoClass::NthChild(n) = pseudo_class {
+                if position != *n {
+                    return Ok(false);
+                }
+            }
+        }
+
+        for attribute in &self.attributes {
+            let value = element.get_current_property_value(attribute.property)?;
+            if !attribute.matches(&value) {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Applies the step's ordinal pseudo-classes to everything it matched.
+    fn pick<E>(&self, mut matches: BTreeMap<Vec<usize>, E>) -> BTreeMap<Vec<usize>, E> {
+        for pseudo_class in &self.pseudo_classes {
+            let keep = match pseudo_class {
+                PseudoClass::First => 1,
+                PseudoClass::Last => matches.len(),
+                PseudoClass::Nth(n) => *n,
+                PseudoClass::NthChild(_) => continue,
+            };
+            matches = match keep.checked_sub(1).and_then(|i| matches.into_iter().nth(i)) {
+                Some((path, element)) => BTreeMap::from([(path, element)]),
+                None => BTreeMap::new(),
+            };
+        }
+        matches
+    }
+}
+
+impl Attribute {
+    fn matches(&self, value: &PropertyValue) -> bool {
+        let text = match value_text(self.property, value) {
+            Some(text) => text,
+            None => return false,
+        };
+
+        match &self.matcher {
+            None => !text.is_empty(),
+            Some(matcher) => matcher.is_match(&text),
+        }
+    }
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, step) in self.steps.iter().enumerate() {
+            match (i, step.combinator) {
+                (0, Combinator::Descendant) => {}
+                (0, Combinator::Child) => write!(f, "> ")?,
+                (_, Combinator::Descendant) => write!(f, " ")?,
+                (_, Combinator::Child) => write!(f, " > ")?,
+            }
+            write!(f, "{step}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.control_type {
+            Some(control_type) => write!(f, "{control_type}")?,
+            None => write!(f, "*")?,
+        }
+        for attribute in &self.attributes {
+            write!(f, "[{}", attribute.property)?;
+            if let Some(matcher) = &attribute.matcher {
+                write!(f, "{}{}", matcher.operator(), Quoted(matcher.pattern()))?;
+            }
+            write!(f, "]")?;
+        }
+        for pseudo_class in &self.pseudo_classes {
+            match pseudo_class {
+                PseudoClass::First => write!(f, ":first")?,
+                PseudoClass::Last => write!(f, ":last")?,
+                PseudoClass::Nth(n) => write!(f, ":nth({n})")?,
+                PseudoClass::NthChild(n) => write!(f, ":nth-child({n})")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Selector {
+    type Err = UiacError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Parser { s, pos: 0 }.parse_selector()
+    }
+}
+
+struct Parser<'a> {
+    s: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.s[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn error_at(&self, position: usize, message: impl Into<String>) -> UiacError {
+        UiacError::InvalidSelector {
+            selector: self.s.to_owned(),
+            position,
+            message: message.into(),
+        }
+    }
+
+    fn error(&self, message: impl Into<String>) -> UiacError {
+        self.error_at(self.pos, message)
+    }
+
+    /// Skips whitespace and returns whether there was any.
+    fn skip_whitespace(&mut self) -> bool {
+        let start = self.pos;
+        self.pos = self.s.len() - self.rest().trim_start().len();
+        self.pos > start
+    }
+
+    fn eat(&mut self, symbol: &str) -> bool {
+        if self.rest().starts_with(symbol) {
+            self.pos += symbol.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: &str) -> UiacResult<()> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected {symbol:?}")))
+        }
+    }
+
+    fn parse_selector(&mut self) -> UiacResult<Selector> {
+        self.skip_whitespace();
+        let mut combinator = if self.eat(">") {
+            self.skip_whitespace();
+            Combinator::Child
+        } else {
+            Combinator::Descendant
+        };
+
+        let mut steps = vec![];
+        loop {
+            steps.push(self.parse_step(combinator)?);
+
+            let had_whitespace = self.skip_whitespace();
+            if self.rest().is_empty() {
+                break;
+            }
+            combinator = if self.eat(">") {
+                self.skip_whitespace();
+                Combinator::Child
+            } else if had_whitespace {
+                Combinator::Descendant
+            } else {
+                return Err(self.error("expected \">\", \"[\", \":\" or whitespace"));
+            };
+        }
+
+        Ok(Selector { steps })
+    }
+
+    fn parse_step(&mut self, combinator: Combinator) -> UiacResult<Step> {
+        let start = self.pos;
+        let control_type = if self.eat("*") {
+            None
+        } else if self.peek().is_some_and(is_name_start) {
+            let mut name = self.parse_name();
+            // Control types without a name are written as they're printed, as
+            // in `Unknown(50100)`.
+            if name == "Unknown" && self.peek() == Some('(') {
+                let len = self
+                    .rest()
+                    .find(')')
+                    .ok_or_else(|| self.error_at(self.s.len(), "expected \")\""))?;
+                self.pos += len + 1;
+                name = &self.s[start..self.pos];
+            }
+            Some(
+                name.parse()
+                    .map_err(|_| self.error_at(start, format!("unknown control type {name:?}")))?,
+            )
+        } else if matches!(self.peek(), Some('[' | ':')) {
+            None
+        } else {
+            return Err(self.error("expected a control type, \"*\", \"[\" or \":\""));
+        };
+
+        let mut step = Step {
+            combinator,
+            control_type,
+            attributes: vec![],
+            pseudo_classes: vec![],
+        };
+
+        loop {
+            if self.eat("[") {
+                step.attributes.push(self.parse_attribute()?);
+            } else if self.eat(":") {
+                step.pseudo_classes.push(self.parse_pseudo_class()?);
+            } else {
+                return Ok(step);
+            }
+        }
+    }
+
+    fn parse_name(&mut self) -> &'a str {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '.' | '-')))
+            .unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn parse_attribute(&mut self) -> UiacResult<Attribute> {
+        self.skip_whitespace();
+        let start = self.pos;
+        if !self.peek().is_some_and(is_name_start) {
+            return Err(self.error("expected a property name"));
+        }
+        let name = self.parse_name();
+        let property: Property = name
+            .parse()
+            .map_err(|_| self.error_at(start, format!("unknown property {name:?}")))?;
+        self.skip_whitespace();
+
+        if self.eat("]") {
+            return Ok(Attribute {
+                property,
+                matcher: None,
+            });
+        }
+
+        let operator_start = self.pos;
+        let operator_len = self
+            .rest()
+            .find(|c: char| !OPERATOR_CHARS.contains(c))
+            .filter(|len| self.rest()[*len..].starts_with('='))
+            .ok_or_else(|| self.error("expected \"]\", \"=\" or an operator such as \"^=\""))?;
+        let operator = &self.rest()[..operator_len];
+        let (kind, flags) = parse_operator(operator).ok_or_else(|| {
+            self.error_at(operator_start, format!("invalid operator \"{operator}=\""))
+        })?;
+        self.pos += operator_len + 1;
+
+        self.skip_whitespace();
+        let value_start = self.pos;
+        let value = self.parse_value()?;
+        self.skip_whitespace();
+        self.expect("]")?;
+
+        let matcher = Matcher::new(kind, value, flags)
+            .map_err(|err| self.error_at(value_start, err.to_string()))?;
+
+        Ok(Attribute {
+            property,
+            matcher: Some(matcher),
+        })
+    }
+
+    /// Parses a value in single or double quotes, or a bare word. In quotes, a
+    /// backslash only escapes the quote or another backslash, so regexes like
+    /// `"\d+"` can be written as-is.
+    fn parse_value(&mut self) -> UiacResult<String> {
+        let start = self.pos;
+        match self.peek() {
+            Some(quote @ ('"' | '\'')) => {
+                self.pos += 1;
+                let mut value = String::new();
+                let mut chars = self.rest().char_indices();
+                while let Some((i, c)) = chars.next() {
+                    match c {
+                        '\\' => match chars.next() {
+                            Some((_, c)) if c == quote || c == '\\' => value.push(c),
+                            Some((_, c)) => {
+                                value.push('\\');
+                                value.push(c);
+                            }
+                            None => break,
+                        },
+                        c if c == quote => {
+                            self.pos += i + 1;
+                            return Ok(value);
+                        }
+                        c => value.push(c),
+                    }
+                }
+                Err(self.error_at(start, "unterminated string"))
+            }
+            _ => {
+                let rest = self.rest();
+                let len = rest
+                    .find(|c: char| c.is_whitespace() || c == ']')
+                    .unwrap_or(rest.len());
+                if len == 0 {
+                    return Err(self.error("expected a value"));
+                }
+                self.pos += len;
+                Ok(rest[..len].to_owned())
+            }
+        }
+    }
+
+    fn parse_pseudo_class(&mut self) -> UiacResult<PseudoClass> {
+        let start = self.pos;
+        match self.parse_name() {
+            "first" => Ok(PseudoClass::First),
+            "last" => Ok(PseudoClass::Last),
+            "nth" => Ok(PseudoClass::Nth(self.parse_position()?)),
+            "nth-child" => Ok(PseudoClass::NthChild(self.parse_position()?)),
+            name => Err(self.error_at(
+                start,
+                format!(
+                    "unknown pseudo-class {name:?}, expected \"first\", \"last\", \"nth\" \
+                     or \"nth-child\""
+                ),
+            )),
+        }
+    }
+
+    fn parse_position(&mut self) -> UiacResult<usize> {
+        self.expect("(")?;
+        self.skip_whitespace();
+        let start = self.pos;
+        let len = self
+            .rest()
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(self.rest().len());
+        let position: usize = self.rest()[..len]
+            .parse()
+            .ok()
+            .filter(|position| *position > 0)
+            .ok_or_else(|| self.error("expected a position, counting from 1"))?;
+        self.pos = start + len;
+        self.skip_whitespace();
+        self.expect(")")?;
+        Ok(position)
+    }
+}
+
+fn is_name_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::matcher::{MatchFlags, MatchKind},
+    };
+
+    #[test]
+    fn selector_from_str_test() -> UiacResult<()> {
+        let selector: Selector =
+            r#"Window[Name="Notepad"] > Pane Edit[AutomationId='15']:nth(2)"#.parse()?;
+
+        assert_eq!(selector.steps.len(), 3);
+        assert_eq!(selector.steps[0].combinator, Combinator::Descendant);
+        assert_eq!(selector.steps[0].control_type, Some(ControlType::Window));
+        assert_eq!(selector.steps[1].combinator, Combinator::Child);
+        assert_eq!(selector.steps[2].combinator, Combinator::Descendant);
+        assert_eq!(
+            selector.steps[2].attributes[0].property,
+            Property::AutomationId
+        );
+        assert_eq!(selector.steps[2].pseudo_classes, [PseudoClass::Nth(2)]);
+        assert_eq!(
+            selector.to_string(),
+            r#"Window[Name="Notepad"] > Pane Edit[AutomationId="15"]:nth(2)"#
+        );
+
+        let selector: Selector =
+            r#" >*[ Name ^= Sys ][Value.Value/="^\d+$"][Name?~_='s*  CLOCK*']:first:nth-child(3) "#
+                .parse()?;
+        assert_eq!(
+            selector.to_string(),
+            r#"> *[Name^="Sys"][Value.Value/="^\\d+$"][Name?~_="s*  CLOCK*"]:first:nth-child(3)"#
+        );
+
+        let selector: Selector = "Unknown(50100) > *[ControlType=Unknown(50101)]".parse()?;
+        assert_eq!(
+            selector.steps[0].control_type,
+            Some(ControlType::Unknown(50100))
+        );
+        assert_eq!(
+            selector.to_string(),
+            r#"Unknown(50100) > *[ControlType="Unknown(50101)"]"#
+        );
+
+        // Values are written as they are, save for quotes and backslashes.
+        let name = "Say \"hi\"\tto C:\\\n\u{200e}them";
+        let selector = Selector {
+            steps: vec![Step {
+                combinator: Combinator::Descendant,
+                control_type: None,
+                attributes: vec![Attribute {
+                    property: Property::Name,
+                    matcher: Some(Matcher::new(
+                        MatchKind::Equals,
+                        name,
+                        MatchFlags::default(),
+                    )?),
+                }],
+                pseudo_classes: vec![],
+            }],
+        };
+        let written = selector.to_string();
+        assert_eq!(
+            written,
+            "*[Name=\"Say \\\"hi\\\"\tto C:\\\\\n\u{200e}them\"]"
+        );
+        let parsed: Selector = written.parse()?;
+        assert_eq!(
+            parsed.steps[0].attributes[0].matcher,
+            selector.steps[0].attributes[0].matcher
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn selector_error_test() {
+        let errors = [
+            ("", 0, "expected a control type"),
+            ("Buton", 0, "unknown control type \"Buton\""),
+            ("Button[Nmae=OK]", 7, "unknown property \"Nmae\""),
+            ("Button[Name=OK", 14, "expected \"]\""),
+            ("Button[Name!=OK]", 11, "expected \"]\", \"=\""),
+            ("Button[Name~*=OK]", 11, "invalid operator \"~*=\""),
+            ("Button[Name=\"OK]", 12, "unterminated string"),
+            ("Button[Name/=\"(\"]", 13, "invalid pattern"),
+            ("Button:nth(0)", 11, "expected a position"),
+            ("Button:second", 7, "unknown pseudo-class \"second\""),
+            ("Pane >", 6, "expected a control type"),
+            ("Unknown(x)", 0, "unknown control type \"Unknown(x)\""),
+            ("Unknown(5", 9, "expected \")\""),
+        ];
+
+        for (selector, position, message) in errors {
+            match selector.parse::<Selector>() {
+                Err(UiacError::InvalidSelector {
+                    position: actual_position,
+                    message: actual_message,
+                    ..
+                }) => {
+                    assert_eq!(actual_position, position, "{selector:?}");
+                    assert!(
+                        actual_message.starts_with(message),
+                        "{selector:?}: {actual_message}"
+                    );
+                }
+                result => panic!("{selector:?} parsed as {result:?}"),
+            }
+        }
+    }
+}
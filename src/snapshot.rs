@@ -0,0 +1,130 @@
+use {
+    crate::{
+        backend::{Element, TreeWalker},
+        memory::Node,
+        traversal::{Event, Order, Traversal, Walk},
+        IndexPath, Property, PropertyValue, UiacError, UiacResult,
+    },
+    serde::{Deserialize, Serialize},
+    std::{collections::BTreeMap, fs, io::Write, path::Path},
+    time::{format_description::well_known::Rfc3339, OffsetDateTime},
+};
+
+/// A captured UIA tree, along with when and where it was captured.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Snapshot {
+    pub uiac_version: String,
+    pub timestamp: String,
+    pub root: String,
+    pub tree: Node,
+}
+
+impl Snapshot {
+    /// Walks the tree under `element` and records it as a snapshot. `root`
+    /// describes where the walk started, e.g. "desktop", and `path` is the
+    /// element's index path, if paths should be recorded.
+    pub(crate) fn capture<W: TreeWalker>(
+        walk: &Walk<W>,
+        element: &W::Element,
+        path: Option<IndexPath>,
+        root: impl Into<String>,
+    ) -> UiacResult<Self> {
+        Self::new(capture_tree(walk, element, path)?, root)
+    }
+
+    /// Records `tree` as a snapshot taken now, from `root`.
+    pub(crate) fn new(tree: Node, root: impl Into<String>) -> UiacResult<Self> {
+        let timestamp = OffsetDateTime::now_utc()
+            .format(&Rfc3339)
+            .map_err(|err| UiacError::Message(err.to_string()))?;
+
+        Ok(Self {
+            uiac_version: env!("CARGO_PKG_VERSION").to_owned(),
+            timestamp,
+            root: root.into(),
+            tree,
+        })
+    }
+
+    pub fn from_json(json: &str) -> UiacResult<Self> {
+        read_json(json)
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> UiacResult<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+}
+
+/// Records `element` and its subtree as the walk goes, with the element's
+/// index path if given.
+pub(crate) fn capture_tree<W: TreeWalker>(
+    walk: &Walk<W>,
+    element: &W::Element,
+    path: Option<IndexPath>,
+) -> UiacResult<Node> {
+    // The nodes entered but not yet left, each to be added to the one below
+    // it once it's complete.
+    let mut open: Vec<Node> = vec![];
+    for event in Traversal::new(walk, element, path, Order::DepthFirst) {
+        match event? {
+            Event::Enter(visit) => open.push(Node {
+                name: visit.element.get_current_name()?,
+                control_type: visit.element.get_current_control_type()?,
+                path: visit.path,
+                properties: recorded_properties(walk, &visit.element)?,
+                children: vec![],
+            }),
+            Event::Leave => {
+                let node = open.pop().expect("left a node that wasn't entered");
+                match open.last_mut() {
+                    Some(parent) => parent.children.push(node),
+                    None => return Ok(node),
+                }
+            }
+        }
+    }
+
+    unreachable!("a depth-first traversal leaves every node it enters")
+}
+
+/// Writes `value`, made of nodes, as JSON. Nodes nest as deep as the tree does,
+/// so the stack is grown onto the heap as serializing needs it rather than
+/// allowed to overflow.
+pub(crate) fn write_json(
+    value: &impl Serialize,
+    pretty: bool,
+    out: &mut impl Write,
+) -> UiacResult<()> {
+    if pretty {
+        let mut serializer = serde_json::Serializer::pretty(out);
+        value.serialize(serde_stacker::Serializer::new(&mut serializer))?;
+    } else {
+        let mut serializer = serde_json::Serializer::new(out);
+        value.serialize(serde_stacker::Serializer::new(&mut serializer))?;
+    }
+    Ok(())
+}
+
+/// Reads `T`, made of nodes, from JSON. serde_json would give up on trees more
+/// than about 60 levels deep, so its limit is lifted and the stack grown onto
+/// the heap as it's needed, as in [`write_json`].
+pub(crate) fn read_json<T: for<'de> Deserialize<'de>>(json: &str) -> UiacResult<T> {
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    deserializer.disable_recursion_limit();
+    let value = T::deserialize(serde_stacker::Deserializer::new(&mut deserializer))?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+/// The values of the walk's properties for `element`, leaving out `Name` and
+/// `ControlType`, which are recorded in fields of their own.
+pub(crate) fn recorded_properties<W: TreeWalker>(
+    walk: &Walk<W>,
+    element: &W::Element,
+) -> UiacResult<BTreeMap<Property, PropertyValue>> {
+    walk.properties
+        .iter()
+        .filter(|property| !matches!(property, Property::Name | Property::ControlType))
+        .map(|property| Ok((*property, element.get_current_property_value(*property)?)))
+        .collect()
+}
@@ -0,0 +1,158 @@
+use {
+    crate::{
+        backend::{Element, TreeWalker},
+        property_value::attribute_text,
+        traversal::{Event, Order, Traversal, Walk},
+        IndexPath, Property, PropertyValue, UiacResult,
+    },
+    std::io::Write,
+};
+
+/// What separates the cells of a row.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Delimiter {
+    Comma,
+    Tab,
+}
+
+/// Writes `element` and its subtree as a table with one row per element, in
+/// `order`, after a header row. Each row has the element's depth below
+/// `element`, its index path and its parent's, its control type and then the
+/// walk's other properties. Paths are left blank if `path` isn't given.
+pub(crate) fn write_table<W: TreeWalker>(
+    walk: &Walk<W>,
+    element: &W::Element,
+    path: Option<IndexPath>,
+    order: Order,
+    delimiter: Delimiter,
+    out: &mut impl Write,
+) -> UiacResult<()> {
+    write_header(walk, delimiter, out)?;
+    write_rows(walk, element, path, order, delimiter, out)
+}
+
+/// Writes the header row of a table of the walk's properties.
+pub(crate) fn write_header<W>(
+    walk: &Walk<W>,
+    delimiter: Delimiter,
+    out: &mut impl Write,
+) -> UiacResult<()> {
+    let mut header = vec![
+        "Depth".to_owned(),
+        "IndexPath".to_owned(),
+        "ParentPath".to_owned(),
+        "ControlType".to_owned(),
+    ];
+    header.extend(columns(walk).map(|property| property.to_string()));
+    write_row(&header, delimiter, out)
+}
+
+/// Writes the rows of `element` and its subtree, as in `write_table`, without
+/// the header. Tables of several subtrees write one header and then the rows
+/// of each.
+pub(crate) fn write_rows<W: TreeWalker>(
+    walk: &Walk<W>,
+    element: &W::Element,
+    path: Option<IndexPath>,
+    order: Order,
+    delimiter: Delimiter,
+    out: &mut impl Write,
+) -> UiacResult<()> {
+    let properties: Vec<Property> = columns(walk).collect();
+    for event in Traversal::new(walk, element, path, order) {
+        if let Event::Enter(visit) = event? {
+            let path = visit.path.as_ref();
+            let mut row = vec![
+                visit.depth.to_string(),
+                path.map(ToString::to_string).unwrap_or_default(),
+                path.and_then(IndexPath::parent)
+                    .map(|parent| parent.to_string())
+                    .unwrap_or_default(),
+                visit.element.get_current_control_type()?.to_string(),
+            ];
+            for property in &properties {
+                let value = visit.element.get_current_property_value(*property)?;
+                row.push(cell_text(*property, &value));
+            }
+            write_row(&row, delimiter, out)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The properties with columns after the control type, which has a column of
+/// its own ahead of them.
+fn columns<'a, W>(walk: &'a Walk<W>) -> impl Iterator<Item = Property> + 'a {
+    walk.properties
+        .iter()
+        .copied()
+        .filter(|property| *property != Property::ControlType)
+}
+
+/// The text of a property's cell, as it reads in page source where it can be.
+/// Properties the element doesn't support are left blank.
+fn cell_text(property: Property, value: &PropertyValue) -> String {
+    match (attribute_text(property, value), value) {
+        (Some(text), _) => text,
+        (None, PropertyValue::Empty | PropertyValue::NotSupported) => String::new(),
+        (None, value) => value.to_string(),
+    }
+}
+
+fn write_row(cells: &[String], delimiter: Delimiter, out: &mut impl Write) -> UiacResult<()> {
+    let cells: Vec<String> = cells.iter().map(|cell| escape(cell, delimiter)).collect();
+    // RFC 4180 ends rows with CRLF, which spreadsheets expect of CSV. TSV is
+    // read by line-based tools as much as by spreadsheets, so its rows end
+    // with plain line feeds.
+    match delimiter {
+        Delimiter::Comma => write!(out, "{}\r\n", cells.join(","))?,
+        Delimiter::Tab => writeln!(out, "{}", cells.join("\t"))?,
+    }
+    Ok(())
+}
+
+/// Escapes a cell. CSV quotes cells with commas, quotes or line breaks in them,
+/// doubling the quotes, while TSV has no quoting, so tabs and line breaks
+/// become spaces. In either, a cell that a spreadsheet would take for a
+/// formula, like a name of `=HYPERLINK(...)`, gets a leading `'` to keep it
+/// text.
+fn escape(cell: &str, delimiter: Delimiter) -> String {
+    let cell = if cell.starts_with(['=', '+', '-', '@']) {
+        format!("'{cell}")
+    } else {
+        cell.to_owned()
+    };
+    match delimiter {
+        Delimiter::Comma if cell.contains([',', '"', '\r', '\n']) => {
+            format!("\"{}\"", cell.replace('"', "\"\""))
+        }
+        Delimiter::Comma => cell,
+        Delimiter::Tab => cell.replace(['\t', '\r', '\n'], " "),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_test() {
+        assert_eq!(escape("plain", Delimiter::Comma), "plain");
+        assert_eq!(escape("a, b", Delimiter::Comma), r#""a, b""#);
+        assert_eq!(escape(r#"say "hi""#, Delimiter::Comma), r#""say ""hi""""#);
+        assert_eq!(escape("two\nlines", Delimiter::Comma), "\"two\nlines\"");
+        assert_eq!(escape("a, b", Delimiter::Tab), "a, b");
+        assert_eq!(escape("a\tb\r\nc", Delimiter::Tab), "a b  c");
+
+        // Formulas are kept from running.
+        assert_eq!(escape("=1+1", Delimiter::Comma), "'=1+1");
+        assert_eq!(escape("+1", Delimiter::Tab), "'+1");
+        assert_eq!(escape("-1", Delimiter::Comma), "'-1");
+        assert_eq!(
+            escape(r#"@SUM(A1, "x")"#, Delimiter::Comma),
+            r#""'@SUM(A1, ""x"")""#
+        );
+        assert_eq!(escape("1=1", Delimiter::Comma), "1=1");
+    }
+}
@@ -0,0 +1,194 @@
+use {
+    crate::{backend::TreeWalker, Filter, IndexPath, Property, UiacResult},
+    std::collections::VecDeque,
+};
+
+/// A walk of the tree below an element, as printed or captured.
+pub(crate) struct Walk<'a, W> {
+    pub walker: &'a W,
+    /// The properties to record for each element, in order.
+    pub properties: &'a [Property],
+    /// How many levels below the starting element to descend, or `None` for
+    /// no limit.
+    pub max_depth: Option<usize>,
+    /// Descendants that don't match are left out, as a walker would leave them
+    /// out, but their own descendants are still walked.
+    pub filter: &'a Filter,
+}
+
+/// The order a traversal visits elements in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Order {
+    /// Each element before its descendants, and those before its next
+    /// sibling.
+    #[default]
+    DepthFirst,
+    /// Level by level.
+    BreadthFirst,
+}
+
+/// An element reached by a traversal.
+pub(crate) struct Visit<E> {
+    pub element: E,
+    /// How many levels below the starting element it is, not counting the
+    /// elements left out by the walk's filter.
+    pub depth: usize,
+    /// Its index path, if the traversal started with one.
+    pub path: Option<IndexPath>,
+}
+
+pub(crate) enum Event<E> {
+    Enter(Visit<E>),
+    /// The traversal is done with the descendants of the last element entered
+    /// that hasn't been left yet. Only depth-first traversals leave elements.
+    Leave,
+}
+
+/// An element whose children the traversal is going through.
+struct Frame<E> {
+    parent: E,
+    /// The last child reached, or `None` before the first.
+    last_child: Option<E>,
+    /// The raw index of the next child, for its index path.
+    index: usize,
+    path: Option<IndexPath>,
+    /// The depth of the children.
+    depth: usize,
+    /// Whether the parent was entered, rather than left out by the filter.
+    entered: bool,
+    /// Whether there are no more children to go through.
+    done: bool,
+}
+
+/// Walks the tree below an element with an explicit stack rather than by
+/// recursion, so that however deep the tree goes, it can't overflow the
+/// thread's stack. The traversal stops after yielding an error.
+pub(crate) struct Traversal<'a, W: TreeWalker> {
+    walk: &'a Walk<'a, W>,
+    order: Order,
+    /// The starting element, until it's been entered.
+    start: Option<(W::Element, Option<IndexPath>)>,
+    /// The frame being gone through is on top. Below it are its ancestors in a
+    /// depth-first traversal, or the elements left out by the filter whose
+    /// children are part of the current level in a breadth-first one.
+    stack: Vec<Frame<W::Element>>,
+    /// The frames for the next levels of a breadth-first traversal.
+    queue: VecDeque<Frame<W::Element>>,
+}
+
+impl<'a, W: TreeWalker> Traversal<'a, W> {
+    /// Starts a traversal at `element`, whose index path is `path` if paths
+    /// should be worked out. In that case, the walker must walk the raw view.
+    pub fn new(
+        walk: &'a Walk<'a, W>,
+        element: &W::Element,
+        path: Option<IndexPath>,
+        order: Order,
+    ) -> Self {
+        Self {
+            walk,
+            order,
+            start: Some((element.clone(), path)),
+            stack: vec![],
+            queue: VecDeque::new(),
+        }
+    }
+
+    /// Enters `element` at `depth`, scheduling its children.
+    fn enter(
+        &mut self,
+        element: W::Element,
+        path: Option<IndexPath>,
+        depth: usize,
+    ) -> Event<W::Element> {
+        let frame = Frame {
+            parent: element.clone(),
+            last_child: None,
+            index: 0,
+            path: path.clone(),
+            depth: depth + 1,
+            entered: true,
+            done: self.walk.max_depth == Some(depth),
+        };
+        match self.order {
+            Order::DepthFirst => self.stack.push(frame),
+            Order::BreadthFirst => self.queue.push_back(frame),
+        }
+
+        Event::Enter(Visit {
+            element,
+            depth,
+            path,
+        })
+    }
+
+    fn step(&mut self) -> UiacResult<Option<Event<W::Element>>> {
+        if let Some((element, path)) = self.start.take() {
+            return Ok(Some(self.enter(element, path, 0)));
+        }
+
+        loop {
+            let Some(frame) = self.stack.last_mut() else {
+                match self.queue.pop_front() {
+                    Some(frame) => {
+                        self.stack.push(frame);
+                        continue;
+                    }
+                    None => return Ok(None),
+                }
+            };
+
+            if frame.done {
+                let entered = frame.entered;
+                self.stack.pop();
+                if entered && self.order == Order::DepthFirst {
+                    return Ok(Some(Event::Leave));
+                }
+                continue;
+            }
+
+            let child_option = match &frame.last_child {
+                None => self.walk.walker.get_first_child_element(&frame.parent)?,
+                Some(last_child) => self.walk.walker.get_next_sibling_element(last_child)?,
+            };
+            let Some(child) = child_option else {
+                frame.done = true;
+                continue;
+            };
+            frame.last_child = Some(child.clone());
+            let path = frame.path.as_ref().map(|path| path.child(frame.index));
+            frame.index += 1;
+            let depth = frame.depth;
+
+            if self.walk.filter.matches(&child)? {
+                return Ok(Some(self.enter(child, path, depth)));
+            }
+            // Go through the children of an element that's left out as if
+            // they were its siblings.
+            self.stack.push(Frame {
+                parent: child,
+                last_child: None,
+                index: 0,
+                path,
+                depth,
+                entered: false,
+                done: false,
+            });
+        }
+    }
+}
+
+impl<W: TreeWalker> Iterator for Traversal<'_, W> {
+    type Item = UiacResult<Event<W::Element>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.step() {
+            Ok(event) => event.map(Ok),
+            Err(err) => {
+                self.stack.clear();
+                self.queue.clear();
+                Some(Err(err))
+            }
+        }
+    }
+}
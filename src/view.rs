@@ -0,0 +1,43 @@
+use crate::{backend::Automation, Filter, Property, PropertyValue, UiacResult};
+
+/// Which of UIA's standard views of the tree to walk.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum View {
+    /// Every element in the tree.
+    #[default]
+    Raw,
+    /// Elements that are interactive or carry information, as screen readers
+    /// see them.
+    Control,
+    /// Elements that carry information, without labels and decoration.
+    Content,
+}
+
+impl View {
+    pub fn condition<A: Automation>(self, automation: &A) -> UiacResult<A::Condition> {
+        match self {
+            View::Raw => automation.get_raw_view_condition(),
+            View::Control => automation.get_control_view_condition(),
+            View::Content => automation.get_content_view_condition(),
+        }
+    }
+
+    /// Creates a walker that sees the view, for use with the walker's
+    /// iterators such as `children` and `descendants`.
+    pub fn walker<A: Automation>(self, automation: &A) -> UiacResult<A::TreeWalker> {
+        automation.create_tree_walker(&self.condition(automation)?)
+    }
+
+    /// A filter for the elements in the view, for checking them in uiac.
+    pub fn filter(self) -> Filter {
+        match self {
+            View::Raw => Filter::True,
+            View::Control => {
+                Filter::property(Property::IsControlElement, PropertyValue::Bool(true))
+            }
+            View::Content => {
+                Filter::property(Property::IsContentElement, PropertyValue::Bool(true))
+            }
+        }
+    }
+}
@@ -1,32 +1,39 @@
 use {
-    crate::{UiacError, UiacResult},
-    std::fmt,
-    windows::Win32::{
-        System::Com::{CoCreateInstance, CLSCTX_ALL, VARENUM, VARIANT, VT_BSTR, VT_I4},
-        UI::Accessibility::{
-            CUIAutomation, IUIAutomation, IUIAutomationCondition, IUIAutomationElement,
-            IUIAutomationTreeWalker, UIA_AppBarControlTypeId, UIA_ButtonControlTypeId,
-            UIA_CalendarControlTypeId, UIA_CheckBoxControlTypeId, UIA_ComboBoxControlTypeId,
-            UIA_CustomControlTypeId, UIA_DataGridControlTypeId, UIA_DataItemControlTypeId,
-            UIA_DocumentControlTypeId, UIA_EditControlTypeId, UIA_GroupControlTypeId,
-            UIA_HeaderControlTypeId, UIA_HeaderItemControlTypeId, UIA_HyperlinkControlTypeId,
-            UIA_ImageControlTypeId, UIA_ListControlTypeId, UIA_ListItemControlTypeId,
-            UIA_MenuBarControlTypeId, UIA_MenuControlTypeId, UIA_MenuItemControlTypeId,
-            UIA_PaneControlTypeId, UIA_ProgressBarControlTypeId, UIA_RadioButtonControlTypeId,
-            UIA_ScrollBarControlTypeId, UIA_SemanticZoomControlTypeId, UIA_SeparatorControlTypeId,
-            UIA_SliderControlTypeId, UIA_SpinnerControlTypeId, UIA_SplitButtonControlTypeId,
-            UIA_StatusBarControlTypeId, UIA_TabControlTypeId, UIA_TabItemControlTypeId,
-            UIA_TableControlTypeId, UIA_TextControlTypeId, UIA_ThumbControlTypeId,
-            UIA_TitleBarControlTypeId, UIA_ToolBarControlTypeId, UIA_ToolTipControlTypeId,
-            UIA_TreeControlTypeId, UIA_TreeItemControlTypeId, UIA_WindowControlTypeId,
-            UIA_CONTROLTYPE_ID, UIA_PROPERTY_ID,
+    crate::{
+        backend, opt_result, ControlType, ElementRef, Property, PropertyConditionFlags,
+        PropertyValue, TreeScope, UiacError, UiacResult,
+    },
+    std::{ffi::c_void, mem::ManuallyDrop},
+    windows::{
+        core::{IUnknown, Interface, BSTR},
+        Win32::{
+            Foundation::POINT,
+            System::{
+                Com::{
+                    CoCreateInstance, CLSCTX_ALL, SAFEARRAY, VARENUM, VARIANT, VT_ARRAY, VT_BOOL,
+                    VT_BSTR, VT_EMPTY, VT_I4, VT_R8, VT_UNKNOWN,
+                },
+                Ole::{
+                    SafeArrayCreateVector, SafeArrayDestroy, SafeArrayGetDim, SafeArrayGetElement,
+                    SafeArrayGetLBound, SafeArrayGetUBound, SafeArrayPutElement, VariantClear,
+                },
+            },
+            UI::Accessibility::{
+                CUIAutomation, IUIAutomation, IUIAutomationCacheRequest, IUIAutomationCondition,
+                IUIAutomationElement, IUIAutomationElementArray, IUIAutomationTreeWalker,
+                PropertyConditionFlags as UIA_PROPERTY_CONDITION_FLAGS,
+                PropertyConditionFlags_IgnoreCase, PropertyConditionFlags_MatchSubstring,
+                PropertyConditionFlags_None, TreeScope as UIA_TREE_SCOPE, TreeScope_Children,
+                TreeScope_Descendants, TreeScope_Subtree, UiaGetReservedMixedAttributeValue,
+                UiaGetReservedNotSupportedValue, UIA_PROPERTY_ID,
+            },
         },
     },
 };
 
 macro_rules! wrapper_fn {
-    ($fn_name:ident, $inner_fn_name:ident, $result_type:ident $(, $arg:ident: $arg_type:ty),*) => {
-        pub fn $fn_name(&self$(, $arg: &$arg_type),*) -> $crate::UiacResult<$result_type> {
+    ($fn_name:ident, $inner_fn_name:ident, $result_type:ident $(, $arg:ident: $arg_type:ty)*) => {
+        fn $fn_name(&self$(, $arg: &$arg_type)*) -> $crate::UiacResult<$result_type> {
             std::result::Result::Ok($result_type {
                 inner: unsafe { self.inner.$inner_fn_name($(&$arg.inner),*) }?,
             })
@@ -35,8 +42,11 @@ macro_rules! wrapper_fn {
 }
 
 macro_rules! wrapper_option_fn {
-    ($fn_name:ident, $inner_fn_name:ident, $result_type:ident $(, $arg:ident: $arg_type:ty),*) => {
-        pub fn $fn_name(&self$(, $arg: &$arg_type),*) -> $crate::UiacResult<std::option::Option<$result_type>> {
+    ($fn_name:ident, $inner_fn_name:ident, $result_type:ident $(, $arg:ident: $arg_type:ty)*) => {
+        fn $fn_name(
+            &self
+            $(, $arg: &$arg_type)*
+        ) -> $crate::UiacResult<std::option::Option<$result_type>> {
             let inner = unsafe { $crate::opt_result(self.inner.$inner_fn_name($(&$arg.inner),*)) }?;
             Ok(match inner {
                 std::option::Option::Some(inner) => std::option::Option::Some($result_type {inner}),
@@ -58,46 +68,297 @@ impl Automation {
             }?,
         })
     }
+}
+
+impl backend::Automation for Automation {
+    type Element = Element;
+    type TreeWalker = TreeWalker;
+    type Condition = Condition;
+    type CacheRequest = CacheRequest;
 
     wrapper_fn!(get_root_element, GetRootElement, Element);
+    wrapper_fn!(get_focused_element, GetFocusedElement, Element);
+
+    fn get_element_from_point(&self, x: i32, y: i32) -> UiacResult<Element> {
+        Ok(Element {
+            inner: unsafe { self.inner.ElementFromPoint(POINT { x, y }) }?,
+        })
+    }
+
+    fn compare_elements(&self, element1: &Element, element2: &Element) -> UiacResult<bool> {
+        Ok(unsafe { self.inner.CompareElements(&element1.inner, &element2.inner) }?.as_bool())
+    }
     wrapper_fn!(
         create_tree_walker,
         CreateTreeWalker,
         TreeWalker,
         condition: Condition
     );
+
+    fn create_cache_request(&self, properties: &[Property]) -> UiacResult<CacheRequest> {
+        let inner = unsafe { self.inner.CreateCacheRequest() }?;
+        for property in properties {
+            unsafe { inner.AddProperty(property.id() as i32) }?;
+        }
+        Ok(CacheRequest { inner })
+    }
+
     wrapper_fn!(create_true_condition, CreateTrueCondition, Condition);
+
+    wrapper_fn!(create_false_condition, CreateFalseCondition, Condition);
+
+    fn create_property_condition_ex(
+        &self,
+        property: Property,
+        value: &PropertyValue,
+        flags: PropertyConditionFlags,
+    ) -> UiacResult<Condition> {
+        let value = Variant::from_property_value(value)?;
+
+        let mut uia_flags = PropertyConditionFlags_None.0;
+        if flags.ignore_case {
+            uia_flags |= PropertyConditionFlags_IgnoreCase.0;
+        }
+        if flags.match_substring {
+            uia_flags |= PropertyConditionFlags_MatchSubstring.0;
+        }
+
+        Ok(Condition {
+            inner: unsafe {
+                self.inner.CreatePropertyConditionEx(
+                    property.id() as i32,
+                    &value.inner,
+                    UIA_PROPERTY_CONDITION_FLAGS(uia_flags),
+                )
+            }?,
+        })
+    }
+
+    wrapper_fn!(
+        create_and_condition,
+        CreateAndCondition,
+        Condition,
+        condition1: Condition,
+        condition2: Condition
+    );
+    wrapper_fn!(
+        create_or_condition,
+        CreateOrCondition,
+        Condition,
+        condition1: Condition,
+        condition2: Condition
+    );
+    wrapper_fn!(
+        create_not_condition,
+        CreateNotCondition,
+        Condition,
+        condition: Condition
+    );
+    wrapper_fn!(get_raw_view_condition, RawViewCondition, Condition);
+    wrapper_fn!(get_control_view_condition, ControlViewCondition, Condition);
+    wrapper_fn!(get_content_view_condition, ContentViewCondition, Condition);
 }
 
+#[derive(Clone)]
 pub struct Element {
     inner: IUIAutomationElement,
 }
 
 impl Element {
-    pub fn get_current_property_value(&self, property_id: UIA_PROPERTY_ID) -> UiacResult<Variant> {
+    pub fn get_current_property_variant(
+        &self,
+        property_id: UIA_PROPERTY_ID,
+    ) -> UiacResult<Variant> {
         Ok(Variant {
             inner: unsafe { self.inner.GetCurrentPropertyValue(property_id.0 as i32) }?,
         })
     }
+
+    /// Like `get_current_property_variant`, but if `ignore_default_value` is
+    /// set, properties the element doesn't support come back as UIA's reserved
+    /// "not supported" value rather than the property's default.
+    pub fn get_current_property_variant_ex(
+        &self,
+        property_id: UIA_PROPERTY_ID,
+        ignore_default_value: bool,
+    ) -> UiacResult<Variant> {
+        Ok(Variant {
+            inner: unsafe {
+                self.inner
+                    .GetCurrentPropertyValueEx(property_id.0 as i32, ignore_default_value)
+            }?,
+        })
+    }
+
+    /// Like `get_current_property_variant_ex`, but reads the value cached
+    /// when the element was fetched.
+    pub fn get_cached_property_variant_ex(
+        &self,
+        property_id: UIA_PROPERTY_ID,
+        ignore_default_value: bool,
+    ) -> UiacResult<Variant> {
+        Ok(Variant {
+            inner: unsafe {
+                self.inner
+                    .GetCachedPropertyValueEx(property_id.0 as i32, ignore_default_value)
+            }?,
+        })
+    }
+}
+
+impl backend::Element for Element {
+    type Condition = Condition;
+
+    fn get_current_property_value(&self, property: Property) -> UiacResult<PropertyValue> {
+        self.get_current_property_variant_ex(UIA_PROPERTY_ID(property.id()), true)?
+            .to_property_value()
+    }
+
+    fn get_cached_property_value(&self, property: Property) -> UiacResult<PropertyValue> {
+        self.get_cached_property_variant_ex(UIA_PROPERTY_ID(property.id()), true)?
+            .to_property_value()
+    }
+
+    fn get_current_name(&self) -> UiacResult<String> {
+        self.get_current_property_variant(UIA_PROPERTY_ID(Property::Name.id()))?
+            .as_string()
+    }
+
+    fn get_current_control_type(&self) -> UiacResult<ControlType> {
+        self.get_current_property_variant(UIA_PROPERTY_ID(Property::ControlType.id()))?
+            .as_control_type()
+    }
+
+    fn find_first(&self, scope: TreeScope, condition: &Condition) -> UiacResult<Option<Element>> {
+        let inner = unsafe {
+            opt_result(
+                self.inner
+                    .FindFirst(uia_tree_scope(scope), &condition.inner),
+            )
+        }?;
+        Ok(inner.map(|inner| Element { inner }))
+    }
+
+    fn find_all(&self, scope: TreeScope, condition: &Condition) -> UiacResult<Vec<Element>> {
+        let array =
+            unsafe { opt_result(self.inner.FindAll(uia_tree_scope(scope), &condition.inner)) }?;
+        match array {
+            Some(array) => element_array_to_vec(&array),
+            None => Ok(vec![]),
+        }
+    }
+}
+
+fn uia_tree_scope(scope: TreeScope) -> UIA_TREE_SCOPE {
+    match scope {
+        TreeScope::Children => TreeScope_Children,
+        TreeScope::Descendants => TreeScope_Descendants,
+        TreeScope::Subtree => TreeScope_Subtree,
+    }
+}
+
+fn element_array_to_vec(array: &IUIAutomationElementArray) -> UiacResult<Vec<Element>> {
+    let length = unsafe { array.Length() }?;
+    (0..length)
+        .map(|index| {
+            Ok(Element {
+                inner: unsafe { array.GetElement(index) }?,
+            })
+        })
+        .collect()
 }
 
 pub struct TreeWalker {
     inner: IUIAutomationTreeWalker,
 }
 
-impl TreeWalker {
+impl backend::TreeWalker for TreeWalker {
+    type Element = Element;
+    type CacheRequest = CacheRequest;
+
+    wrapper_option_fn!(
+        get_parent_element,
+        GetParentElement,
+        Element,
+        element: Element
+    );
     wrapper_option_fn!(
         get_first_child_element,
         GetFirstChildElement,
         Element,
         element: Element
     );
+    wrapper_option_fn!(
+        get_last_child_element,
+        GetLastChildElement,
+        Element,
+        element: Element
+    );
     wrapper_option_fn!(
         get_next_sibling_element,
         GetNextSiblingElement,
         Element,
         element: Element
     );
+    wrapper_option_fn!(
+        get_previous_sibling_element,
+        GetPreviousSiblingElement,
+        Element,
+        element: Element
+    );
+    wrapper_option_fn!(
+        normalize_element,
+        NormalizeElement,
+        Element,
+        element: Element
+    );
+
+    wrapper_option_fn!(
+        get_parent_element_build_cache,
+        GetParentElementBuildCache,
+        Element,
+        element: Element,
+        cache_request: CacheRequest
+    );
+    wrapper_option_fn!(
+        get_first_child_element_build_cache,
+        GetFirstChildElementBuildCache,
+        Element,
+        element: Element,
+        cache_request: CacheRequest
+    );
+    wrapper_option_fn!(
+        get_last_child_element_build_cache,
+        GetLastChildElementBuildCache,
+        Element,
+        element: Element,
+        cache_request: CacheRequest
+    );
+    wrapper_option_fn!(
+        get_next_sibling_element_build_cache,
+        GetNextSiblingElementBuildCache,
+        Element,
+        element: Element,
+        cache_request: CacheRequest
+    );
+    wrapper_option_fn!(
+        get_previous_sibling_element_build_cache,
+        GetPreviousSiblingElementBuildCache,
+        Element,
+        element: Element,
+        cache_request: CacheRequest
+    );
+    wrapper_option_fn!(
+        normalize_element_build_cache,
+        NormalizeElementBuildCache,
+        Element,
+        element: Element,
+        cache_request: CacheRequest
+    );
+}
+
+pub struct CacheRequest {
+    inner: IUIAutomationCacheRequest,
 }
 
 pub struct Condition {
@@ -109,6 +370,52 @@ pub struct Variant {
 }
 
 impl Variant {
+    /// Encodes a property value so it can be passed to UIA, e.g. in a property
+    /// condition. Element references and UIA's reserved values can't be
+    /// encoded.
+    pub fn from_property_value(value: &PropertyValue) -> UiacResult<Self> {
+        let mut inner = VARIANT::default();
+
+        unsafe {
+            let variant = &mut inner.Anonymous.Anonymous;
+            match value {
+                PropertyValue::Empty => variant.vt = VT_EMPTY,
+                PropertyValue::Bool(b) => {
+                    variant.vt = VT_BOOL;
+                    // VARIANT_TRUE is all bits set.
+                    variant.Anonymous.boolVal = if *b { -1 } else { 0 };
+                }
+                PropertyValue::Int(i) => {
+                    variant.vt = VT_I4;
+                    variant.Anonymous.lVal = *i;
+                }
+                PropertyValue::Double(d) => {
+                    variant.vt = VT_R8;
+                    variant.Anonymous.dblVal = *d;
+                }
+                PropertyValue::String(s) => {
+                    variant.vt = VT_BSTR;
+                    variant.Anonymous.bstrVal = ManuallyDrop::new(BSTR::from(s));
+                }
+                PropertyValue::IntArray(a) => {
+                    variant.vt = VARENUM(VT_ARRAY.0 | VT_I4.0);
+                    variant.Anonymous.parray = vec_to_safe_array(VT_I4, a)?;
+                }
+                PropertyValue::DoubleArray(a) => {
+                    variant.vt = VARENUM(VT_ARRAY.0 | VT_R8.0);
+                    variant.Anonymous.parray = vec_to_safe_array(VT_R8, a)?;
+                }
+                _ => {
+                    return Err(UiacError::Message(format!(
+                        "can't pass {value} to UI Automation"
+                    )))
+                }
+            }
+        }
+
+        Ok(Self { inner })
+    }
+
     pub fn vt(&self) -> VARENUM {
         unsafe { self.inner.Anonymous.Anonymous.vt }
     }
@@ -128,196 +435,146 @@ impl Variant {
         }
 
         let l_val = unsafe { self.inner.Anonymous.Anonymous.Anonymous.lVal };
-        Ok(ControlType::new(UIA_CONTROLTYPE_ID(l_val as u32)))
+        Ok(ControlType::new(l_val as u32))
+    }
+
+    /// Decodes the variant into a backend-neutral property value.
+    pub fn to_property_value(&self) -> UiacResult<PropertyValue> {
+        let value = unsafe { &self.inner.Anonymous.Anonymous.Anonymous };
+        let vt = self.vt();
+
+        Ok(if vt == VT_EMPTY {
+            PropertyValue::Empty
+        } else if vt == VT_BOOL {
+            PropertyValue::Bool(unsafe { value.boolVal } != 0)
+        } else if vt == VT_I4 {
+            PropertyValue::Int(unsafe { value.lVal })
+        } else if vt == VT_R8 {
+            PropertyValue::Double(unsafe { value.dblVal })
+        } else if vt == VT_BSTR {
+            PropertyValue::String(self.as_string()?)
+        } else if vt == VT_UNKNOWN {
+            match unsafe { &*value.punkVal } {
+                Some(unknown) => unknown_to_property_value(unknown)?,
+                None => PropertyValue::Empty,
+            }
+        } else if vt == VARENUM(VT_ARRAY.0 | VT_I4.0) {
+            PropertyValue::IntArray(unsafe { safe_array_to_vec(value.parray) }?)
+        } else if vt == VARENUM(VT_ARRAY.0 | VT_R8.0) {
+            PropertyValue::DoubleArray(unsafe { safe_array_to_vec(value.parray) }?)
+        } else if vt == VARENUM(VT_ARRAY.0 | VT_BSTR.0) {
+            let strings: Vec<BSTR> = unsafe { safe_array_to_vec(value.parray) }?;
+            PropertyValue::StringArray(strings.iter().map(ToString::to_string).collect())
+        } else {
+            return Err(UiacError::InvalidVariantType);
+        })
     }
 }
 
-#[derive(Debug, Eq, PartialEq)]
-pub enum ControlType {
-    AppBar,
-    Button,
-    Calendar,
-    CheckBox,
-    ComboBox,
-    Custom,
-    DataGrid,
-    DataItem,
-    Document,
-    Edit,
-    Group,
-    Header,
-    HeaderItem,
-    Hyperlink,
-    Image,
-    List,
-    ListItem,
-    MenuBar,
-    Menu,
-    MenuItem,
-    Pane,
-    ProgressBar,
-    RadioButton,
-    ScrollBar,
-    SemanticZoom,
-    Separator,
-    Slider,
-    Spinner,
-    SplitButton,
-    StatusBar,
-    Tab,
-    TabItem,
-    Table,
-    Text,
-    Thumb,
-    TitleBar,
-    ToolBar,
-    ToolTip,
-    Tree,
-    TreeItem,
-    Window,
+impl Drop for Variant {
+    fn drop(&mut self) {
+        // There's nothing useful to do if clearing fails while dropping.
+        let _ = unsafe { VariantClear(&mut self.inner) };
+    }
 }
 
-impl ControlType {
-    fn new(id: UIA_CONTROLTYPE_ID) -> Self {
-        if id == UIA_AppBarControlTypeId {
-            ControlType::AppBar
-        } else if id == UIA_ButtonControlTypeId {
-            ControlType::Button
-        } else if id == UIA_CalendarControlTypeId {
-            ControlType::Calendar
-        } else if id == UIA_CheckBoxControlTypeId {
-            ControlType::CheckBox
-        } else if id == UIA_ComboBoxControlTypeId {
-            ControlType::ComboBox
-        } else if id == UIA_CustomControlTypeId {
-            ControlType::Custom
-        } else if id == UIA_DataGridControlTypeId {
-            ControlType::DataGrid
-        } else if id == UIA_DataItemControlTypeId {
-            ControlType::DataItem
-        } else if id == UIA_DocumentControlTypeId {
-            ControlType::Document
-        } else if id == UIA_EditControlTypeId {
-            ControlType::Edit
-        } else if id == UIA_GroupControlTypeId {
-            ControlType::Group
-        } else if id == UIA_HeaderControlTypeId {
-            ControlType::Header
-        } else if id == UIA_HeaderItemControlTypeId {
-            ControlType::HeaderItem
-        } else if id == UIA_HyperlinkControlTypeId {
-            ControlType::Hyperlink
-        } else if id == UIA_ImageControlTypeId {
-            ControlType::Image
-        } else if id == UIA_ListControlTypeId {
-            ControlType::List
-        } else if id == UIA_ListItemControlTypeId {
-            ControlType::ListItem
-        } else if id == UIA_MenuBarControlTypeId {
-            ControlType::MenuBar
-        } else if id == UIA_MenuControlTypeId {
-            ControlType::Menu
-        } else if id == UIA_MenuItemControlTypeId {
-            ControlType::MenuItem
-        } else if id == UIA_PaneControlTypeId {
-            ControlType::Pane
-        } else if id == UIA_ProgressBarControlTypeId {
-            ControlType::ProgressBar
-        } else if id == UIA_RadioButtonControlTypeId {
-            ControlType::RadioButton
-        } else if id == UIA_ScrollBarControlTypeId {
-            ControlType::ScrollBar
-        } else if id == UIA_SemanticZoomControlTypeId {
-            ControlType::SemanticZoom
-        } else if id == UIA_SeparatorControlTypeId {
-            ControlType::Separator
-        } else if id == UIA_SliderControlTypeId {
-            ControlType::Slider
-        } else if id == UIA_SpinnerControlTypeId {
-            ControlType::Spinner
-        } else if id == UIA_SplitButtonControlTypeId {
-            ControlType::SplitButton
-        } else if id == UIA_StatusBarControlTypeId {
-            ControlType::StatusBar
-        } else if id == UIA_TabControlTypeId {
-            ControlType::Tab
-        } else if id == UIA_TabItemControlTypeId {
-            ControlType::TabItem
-        } else if id == UIA_TableControlTypeId {
-            ControlType::Table
-        } else if id == UIA_TextControlTypeId {
-            ControlType::Text
-        } else if id == UIA_ThumbControlTypeId {
-            ControlType::Thumb
-        } else if id == UIA_TitleBarControlTypeId {
-            ControlType::TitleBar
-        } else if id == UIA_ToolBarControlTypeId {
-            ControlType::ToolBar
-        } else if id == UIA_ToolTipControlTypeId {
-            ControlType::ToolTip
-        } else if id == UIA_TreeControlTypeId {
-            ControlType::Tree
-        } else if id == UIA_TreeItemControlTypeId {
-            ControlType::TreeItem
-        } else if id == UIA_WindowControlTypeId {
-            ControlType::Window
-        } else {
-            panic!("invalid control type id {}", id.0);
-        }
+/// Decodes a `VT_UNKNOWN` value, which is either one of UIA's reserved
+/// sentinels or a reference to one or more other elements.
+fn unknown_to_property_value(unknown: &IUnknown) -> UiacResult<PropertyValue> {
+    if *unknown == unsafe { UiaGetReservedNotSupportedValue() }? {
+        return Ok(PropertyValue::NotSupported);
+    }
+    if *unknown == unsafe { UiaGetReservedMixedAttributeValue() }? {
+        return Ok(PropertyValue::Mixed);
+    }
+
+    if let Ok(inner) = unknown.cast::<IUIAutomationElement>() {
+        return Ok(PropertyValue::Element(element_ref(&Element { inner })?));
+    }
+
+    if let Ok(array) = unknown.cast::<IUIAutomationElementArray>() {
+        return Ok(PropertyValue::ElementArray(
+            element_array_to_vec(&array)?
+                .iter()
+                .map(element_ref)
+                .collect::<UiacResult<_>>()?,
+        ));
+    }
+
+    Err(UiacError::InvalidVariantType)
+}
+
+fn element_ref(element: &Element) -> UiacResult<ElementRef> {
+    Ok(ElementRef {
+        name: backend::Element::get_current_name(element)?,
+        control_type: backend::Element::get_current_control_type(element)?,
+    })
+}
+
+/// Copies the elements of a one-dimensional `SAFEARRAY` into a `Vec`. The
+/// caller must make sure `T` matches the array's element type.
+unsafe fn safe_array_to_vec<T: Default>(array: *const SAFEARRAY) -> UiacResult<Vec<T>> {
+    if SafeArrayGetDim(array) != 1 {
+        return Err(UiacError::InvalidVariantType);
     }
+
+    let lower_bound = SafeArrayGetLBound(array, 1)?;
+    let upper_bound = SafeArrayGetUBound(array, 1)?;
+
+    (lower_bound..=upper_bound)
+        .map(|index| {
+            let mut element = T::default();
+            SafeArrayGetElement(array, &index, &mut element as *mut T as *mut c_void)?;
+            Ok(element)
+        })
+        .collect()
 }
 
-impl fmt::Display for ControlType {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            ControlType::AppBar => write!(f, "AppBar"),
-            ControlType::Button => write!(f, "Button"),
-            ControlType::Calendar => write!(f, "Calendar"),
-            ControlType::CheckBox => write!(f, "CheckBox"),
-            ControlType::ComboBox => write!(f, "ComboBox"),
-            ControlType::Custom => write!(f, "Custom"),
-            ControlType::DataGrid => write!(f, "DataGrid"),
-            ControlType::DataItem => write!(f, "DataItem"),
-            ControlType::Document => write!(f, "Document"),
-            ControlType::Edit => write!(f, "Edit"),
-            ControlType::Group => write!(f, "Group"),
-            ControlType::Header => write!(f, "Header"),
-            ControlType::HeaderItem => write!(f, "HeaderItem"),
-            ControlType::Hyperlink => write!(f, "Hyperlink"),
-            ControlType::Image => write!(f, "Image"),
-            ControlType::List => write!(f, "List"),
-            ControlType::ListItem => write!(f, "ListItem"),
-            ControlType::MenuBar => write!(f, "MenuBar"),
-            ControlType::Menu => write!(f, "Menu"),
-            ControlType::MenuItem => write!(f, "MenuItem"),
-            ControlType::Pane => write!(f, "Pane"),
-            ControlType::ProgressBar => write!(f, "ProgressBar"),
-            ControlType::RadioButton => write!(f, "RadioButton"),
-            ControlType::ScrollBar => write!(f, "ScrollBar"),
-            ControlType::SemanticZoom => write!(f, "SemanticZoom"),
-            ControlType::Separator => write!(f, "Separator"),
-            ControlType::Slider => write!(f, "Slider"),
-            ControlType::Spinner => write!(f, "Spinner"),
-            ControlType::SplitButton => write!(f, "SplitButton"),
-            ControlType::StatusBar => write!(f, "StatusBar"),
-            ControlType::Tab => write!(f, "Tab"),
-            ControlType::TabItem => write!(f, "TabItem"),
-            ControlType::Table => write!(f, "Table"),
-            ControlType::Text => write!(f, "Text"),
-            ControlType::Thumb => write!(f, "Thumb"),
-            ControlType::TitleBar => write!(f, "TitleBar"),
-            ControlType::ToolBar => write!(f, "ToolBar"),
-            ControlType::ToolTip => write!(f, "ToolTip"),
-            ControlType::Tree => write!(f, "Tree"),
-            ControlType::TreeItem => write!(f, "TreeItem"),
-            ControlType::Window => write!(f, "Window"),
+/// Copies a slice into a new one-dimensional `SAFEARRAY`, which the caller
+/// then owns. `vt` must match `T`.
+unsafe fn vec_to_safe_array<T>(vt: VARENUM, values: &[T]) -> UiacResult<*mut SAFEARRAY> {
+    let array = SafeArrayCreateVector(vt, 0, values.len() as u32);
+    if array.is_null() {
+        return Err(UiacError::Message(
+            "couldn't allocate a SAFEARRAY".to_owned(),
+        ));
+    }
+
+    for (index, value) in values.iter().enumerate() {
+        if let Err(error) =
+            SafeArrayPutElement(array, &(index as i32), value as *const T as *const c_void)
+        {
+            let _ = SafeArrayDestroy(array);
+            return Err(error.into());
         }
     }
+
+    Ok(array)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use {
+        super::*,
+        windows::Win32::UI::Accessibility::{
+            UIA_AppBarControlTypeId, UIA_AutomationIdPropertyId, UIA_BoundingRectanglePropertyId,
+            UIA_ButtonControlTypeId, UIA_CalendarControlTypeId, UIA_CheckBoxControlTypeId,
+            UIA_ComboBoxControlTypeId, UIA_CustomControlTypeId, UIA_DataGridControlTypeId,
+            UIA_DataItemControlTypeId, UIA_DocumentControlTypeId, UIA_EditControlTypeId,
+            UIA_GroupControlTypeId, UIA_HeaderControlTypeId, UIA_HeaderItemControlTypeId,
+            UIA_HyperlinkControlTypeId, UIA_ImageControlTypeId, UIA_IsDialogPropertyId,
+            UIA_LegacyIAccessibleNamePropertyId, UIA_ListControlTypeId, UIA_ListItemControlTypeId,
+            UIA_MenuBarControlTypeId, UIA_MenuControlTypeId, UIA_MenuItemControlTypeId,
+            UIA_NamePropertyId, UIA_PaneControlTypeId, UIA_ProgressBarControlTypeId,
+            UIA_RadioButtonControlTypeId, UIA_RuntimeIdPropertyId, UIA_ScrollBarControlTypeId,
+            UIA_SemanticZoomControlTypeId, UIA_SeparatorControlTypeId, UIA_SliderControlTypeId,
+            UIA_SpinnerControlTypeId, UIA_SplitButtonControlTypeId, UIA_StatusBarControlTypeId,
+            UIA_TabControlTypeId, UIA_TabItemControlTypeId, UIA_TableControlTypeId,
+            UIA_TextControlTypeId, UIA_ThumbControlTypeId, UIA_TitleBarControlTypeId,
+            UIA_ToolBarControlTypeId, UIA_ToolTipControlTypeId, UIA_TreeControlTypeId,
+            UIA_TreeItemControlTypeId, UIA_ValueValuePropertyId, UIA_WindowControlTypeId,
+        },
+    };
 
     #[test]
     fn control_type_new_test() {
@@ -366,58 +623,52 @@ mod tests {
         ];
 
         for (from, to) in mappings {
-            assert_eq!(ControlType::new(from), to);
+            assert_eq!(ControlType::new(from.0), to);
         }
     }
 
     #[test]
-    fn control_type_display_test() {
+    fn property_new_test() {
         let mappings = [
-            (ControlType::AppBar, "AppBar"),
-            (ControlType::Button, "Button"),
-            (ControlType::Calendar, "Calendar"),
-            (ControlType::CheckBox, "CheckBox"),
-            (ControlType::ComboBox, "ComboBox"),
-            (ControlType::Custom, "Custom"),
-            (ControlType::DataGrid, "DataGrid"),
-            (ControlType::DataItem, "DataItem"),
-            (ControlType::Document, "Document"),
-            (ControlType::Edit, "Edit"),
-            (ControlType::Group, "Group"),
-            (ControlType::Header, "Header"),
-            (ControlType::HeaderItem, "HeaderItem"),
-            (ControlType::Hyperlink, "Hyperlink"),
-            (ControlType::Image, "Image"),
-            (ControlType::List, "List"),
-            (ControlType::ListItem, "ListItem"),
-            (ControlType::MenuBar, "MenuBar"),
-            (ControlType::Menu, "Menu"),
-            (ControlType::MenuItem, "MenuItem"),
-            (ControlType::Pane, "Pane"),
-            (ControlType::ProgressBar, "ProgressBar"),
-            (ControlType::RadioButton, "RadioButton"),
-            (ControlType::ScrollBar, "ScrollBar"),
-            (ControlType::SemanticZoom, "SemanticZoom"),
-            (ControlType::Separator, "Separator"),
-            (ControlType::Slider, "Slider"),
-            (ControlType::Spinner, "Spinner"),
-            (ControlType::SplitButton, "SplitButton"),
-            (ControlType::StatusBar, "StatusBar"),
-            (ControlType::Tab, "Tab"),
-            (ControlType::TabItem, "TabItem"),
-            (ControlType::Table, "Table"),
-            (ControlType::Text, "Text"),
-            (ControlType::Thumb, "Thumb"),
-            (ControlType::TitleBar, "TitleBar"),
-            (ControlType::ToolBar, "ToolBar"),
-            (ControlType::ToolTip, "ToolTip"),
-            (ControlType::Tree, "Tree"),
-            (ControlType::TreeItem, "TreeItem"),
-            (ControlType::Window, "Window"),
+            (UIA_RuntimeIdPropertyId, Property::RuntimeId),
+            (UIA_BoundingRectanglePropertyId, Property::BoundingRectangle),
+            (UIA_NamePropertyId, Property::Name),
+            (UIA_AutomationIdPropertyId, Property::AutomationId),
+            (UIA_ValueValuePropertyId, Property::ValueValue),
+            (
+                UIA_LegacyIAccessibleNamePropertyId,
+                Property::LegacyIAccessibleName,
+            ),
+            (UIA_IsDialogPropertyId, Property::IsDialog),
         ];
 
-        for (control_type, string) in mappings {
-            assert_eq!(control_type.to_string(), string);
+        for (from, to) in mappings {
+            assert_eq!(Property::new(from.0), Some(to));
         }
     }
+
+    #[test]
+    fn variant_round_trip_test() -> UiacResult<()> {
+        let values = [
+            PropertyValue::Empty,
+            PropertyValue::Bool(true),
+            PropertyValue::Bool(false),
+            PropertyValue::Int(50000),
+            PropertyValue::Double(1.5),
+            PropertyValue::String("OK".to_owned()),
+            PropertyValue::IntArray(vec![42, 1, 7]),
+            PropertyValue::DoubleArray(vec![0.0, 1040.0, 1920.0, 40.0]),
+        ];
+
+        for value in values {
+            assert_eq!(
+                Variant::from_property_value(&value)?.to_property_value()?,
+                value
+            );
+        }
+
+        assert!(Variant::from_property_value(&PropertyValue::NotSupported).is_err());
+
+        Ok(())
+    }
 }
@@ -0,0 +1,1237 @@
+use {
+    crate::{
+        backend::{Element, TreeWalker},
+        matcher::{MatchFlags, MatchKind, Matcher},
+        property_value::attribute_text,
+        ControlType, Property, UiacError, UiacResult,
+    },
+    std::{cmp::Ordering, collections::HashMap, fmt, str::FromStr},
+};
+
+/// A query in a subset of XPath 1.0, such as
+/// `//Window[@Name='Calculator']//Button[@AutomationId='num7Button']`.
+///
+/// Elements are named after their control type and UIA properties are their
+/// attributes. Boolean attributes read as "True" or "False" and control types
+/// by name, as in WinAppDriver's page source. The document node sits above
+/// the root element, so `/Pane` matches the root if it's a pane.
+///
+/// Supported are absolute and relative location paths with `//`, `.` and `..`
+/// abbreviations; the child, descendant, descendant-or-self, self, parent,
+/// ancestor, ancestor-or-self, following-sibling, preceding-sibling and
+/// attribute axes; `*`, `node()` and control type node tests; predicates;
+/// unions; `and`, `or` and comparisons; and the functions `true`, `false`,
+/// `not`, `boolean`, `string`, `number`, `name`, `position`, `last`, `count`,
+/// `contains`, `starts-with`, `string-length` and `normalize-space`. From
+/// XPath 2.0 there's `matches(string, regex, flags)`, where the flags may be
+/// `i` to ignore case. Arithmetic and variables aren't supported.
+#[derive(Clone, Debug)]
+pub struct XPath {
+    source: String,
+    expr: Expr,
+}
+
+/// The result of evaluating an XPath.
+#[derive(Clone, Debug, PartialEq)]
+pub enum XPathValue<E> {
+    /// Matched elements, in document order.
+    Elements(Vec<E>),
+    /// The values of matched attributes, in document order.
+    Attributes(Vec<String>),
+    String(String),
+    Number(f64),
+    Bool(bool),
+}
+
+impl XPath {
+    /// Evaluates the XPath against the tree below `root`, as seen through
+    /// `walker`.
+    pub fn evaluate<W: TreeWalker>(
+        &self,
+        walker: &W,
+        root: &W::Element,
+    ) -> UiacResult<XPathValue<W::Element>> {
+        let mut document = Document::new(walker, root);
+        let context = Context {
+            node: 0,
+            position: 1,
+            size: 1,
+        };
+
+        Ok(match document.eval(&self.expr, &context)? {
+            Value::Nodes(nodes) => XPathValue::Elements(
+                nodes
+                    .iter()
+                    .filter_map(|node| document.element(*node).cloned())
+                    .collect(),
+            ),
+            Value::Attributes(values) => XPathValue::Attributes(values),
+            Value::String(s) => XPathValue::String(s),
+            Value::Number(n) => XPathValue::Number(n),
+            Value::Bool(b) => XPathValue::Bool(b),
+        })
+    }
+
+    /// Finds the elements the XPath selects, or fails if it doesn't select
+    /// elements.
+    pub fn find_all<W: TreeWalker>(
+        &self,
+        walker: &W,
+        root: &W::Element,
+    ) -> UiacResult<Vec<W::Element>> {
+        match self.evaluate(walker, root)? {
+            XPathValue::Elements(elements) => Ok(elements),
+            _ => Err(UiacError::Message(format!(
+                "XPath {self} doesn't select elements"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for XPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl FromStr for XPath {
+    type Err = UiacError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            source: s,
+            tokens: tokenize(s)?,
+            index: 0,
+        };
+        let expr = parser.parse_or()?;
+        if parser.index < parser.tokens.len() {
+            return Err(parser.error("expected the end of the expression"));
+        }
+
+        Ok(Self {
+            source: s.to_owned(),
+            expr,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Compare(Box<Expr>, CompareOp, Box<Expr>),
+    Union(Box<Expr>, Box<Expr>),
+    Path(LocationPath),
+    Literal(String),
+    Number(f64),
+    Function(Function, Vec<Expr>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CompareOp {
+    Eq,
+    NotEq,
+    Less,
+    LessEq,
+    Greater,
+    GreaterEq,
+}
+
+#[derive(Clone, Debug)]
+struct LocationPath {
+    absolute: bool,
+    steps: Vec<Step>,
+    /// The attribute selected by a final `@Property` step.
+    attribute: Option<Property>,
+}
+
+#[derive(Clone, Debug)]
+struct Step {
+    axis: Axis,
+    test: NodeTest,
+    predicates: Vec<Expr>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Axis {
+    Child,
+    Descendant,
+    DescendantOrSelf,
+    Self_,
+    Parent,
+    Ancestor,
+    AncestorOrSelf,
+    FollowingSibling,
+    PrecedingSibling,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum NodeTest {
+    /// `node()`, which also matches the document node.
+    Node,
+    /// `*`, which matches any element.
+    Element,
+    ControlType(ControlType),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Function {
+    True,
+    False,
+    Not,
+    Boolean,
+    String,
+    Number,
+    Name,
+    Position,
+    Last,
+    Count,
+    Contains,
+    StartsWith,
+    StringLength,
+    NormalizeSpace,
+    Matches,
+}
+
+impl Function {
+    const ALL: [(&'static str, Function, usize, usize); 15] = [
+        ("true", Function::True, 0, 0),
+        ("false", Function::False, 0, 0),
+        ("not", Function::Not, 1, 1),
+        ("boolean", Function::Boolean, 1, 1),
+        ("string", Function::String, 0, 1),
+        ("number", Function::Number, 0, 1),
+        ("name", Function::Name, 0, 1),
+        ("position", Function::Position, 0, 0),
+        ("last", Function::Last, 0, 0),
+        ("count", Function::Count, 1, 1),
+        ("contains", Function::Contains, 2, 2),
+        ("starts-with", Function::StartsWith, 2, 2),
+        ("string-length", Function::StringLength, 0, 1),
+        ("normalize-space", Function::NormalizeSpace, 0, 1),
+        ("matches", Function::Matches, 2, 3),
+    ];
+}
+
+/// A node is identified by its place in the document's arena: the document
+/// node is 0, the root element 1, and the rest are numbered as they're reached.
+type NodeId = usize;
+
+enum Value {
+    Nodes(Vec<NodeId>),
+    Attributes(Vec<String>),
+    String(String),
+    Number(f64),
+    Bool(bool),
+}
+
+struct Context {
+    node: NodeId,
+    position: usize,
+    size: usize,
+}
+
+struct Node<E> {
+    /// `None` for the document node.
+    element: Option<E>,
+    parent: Option<NodeId>,
+    /// Its position among its parent's children.
+    index: usize,
+    depth: usize,
+    /// Its ancestors 1, 2, 4, 8 and so on levels up, so that where two nodes'
+    /// ancestries meet can be found without climbing a level at a time.
+    jumps: Vec<NodeId>,
+    /// Its children, once they've been fetched.
+    children: Option<Vec<NodeId>>,
+}
+
+/// The part of the tree visited so far. Elements are fetched from the walker
+/// the first time their parent's children are needed.
+struct Document<'a, W: TreeWalker> {
+    walker: &'a W,
+    nodes: Vec<Node<W::Element>>,
+    /// The regexes `matches()` has compiled, by pattern and flags.
+    matchers: HashMap<(String, String), Matcher>,
+}
+
+impl<'a, W: TreeWalker> Document<'a, W> {
+    fn new(walker: &'a W, root: &W::Element) -> Self {
+        let document = Node {
+            element: None,
+            parent: None,
+            index: 0,
+            depth: 0,
+            jumps: vec![],
+            children: Some(vec![1]),
+        };
+        let root = Node {
+            element: Some(root.clone()),
+            parent: Some(0),
+            index: 0,
+            depth: 1,
+            jumps: vec![0],
+            children: None,
+        };
+        Self {
+            walker,
+            nodes: vec![document, root],
+            matchers: HashMap::new(),
+        }
+    }
+
+    fn add(&mut self, element: W::Element, parent: NodeId, index: usize) -> NodeId {
+        let mut jumps = vec![parent];
+        while let Some(&ancestor) = self.nodes[jumps[jumps.len() - 1]]
+            .jumps
+            .get(jumps.len() - 1)
+        {
+            jumps.push(ancestor);
+        }
+        self.nodes.push(Node {
+            element: Some(element),
+            parent: Some(parent),
+            index,
+            depth: self.nodes[parent].depth + 1,
+            jumps,
+            children: None,
+        });
+        self.nodes.len() - 1
+    }
+
+    fn children(&mut self, node: NodeId) -> UiacResult<Vec<NodeId>> {
+        if let Some(children) = &self.nodes[node].children {
+            return Ok(children.clone());
+        }
+
+        let mut children = vec![];
+        if let Some(element) = self.nodes[node].element.clone() {
+            for child in self.walker.children(&element) {
+                children.push(self.add(child?, node, children.len()));
+            }
+        }
+        self.nodes[node].children = Some(children.clone());
+        Ok(children)
+    }
+
+    /// Adds the descendants of `node` to `descendants` in document order. The
+    /// nodes still to visit are kept on a stack, with the next one on top.
+    fn descendants(&mut self, node: NodeId, descendants: &mut Vec<NodeId>) -> UiacResult<()> {
+        let mut stack: Vec<NodeId> = self.children(node)?.into_iter().rev().collect();
+        while let Some(node) = stack.pop() {
+            stack.extend(self.children(node)?.into_iter().rev());
+            descendants.push(node);
+        }
+        Ok(())
+    }
+
+    /// The ancestor `levels` levels above `node`.
+    fn ancestor(&self, mut node: NodeId, levels: usize) -> NodeId {
+        let mut bit = 0;
+        while levels >> bit != 0 {
+            if levels & (1 << bit) != 0 {
+                node = self.nodes[node].jumps[bit];
+            }
+            bit += 1;
+        }
+        node
+    }
+
+    /// Compares nodes in document order, where ancestors come before their
+    /// descendants and otherwise the nodes go in the order of the ancestors'
+    /// children where their ancestries part.
+    fn document_order(&self, node1: NodeId, node2: NodeId) -> Ordering {
+        let (depth1, depth2) = (self.nodes[node1].depth, self.nodes[node2].depth);
+        let mut node1 = self.ancestor(node1, depth1.saturating_sub(depth2));
+        let mut node2 = self.ancestor(node2, depth2.saturating_sub(depth1));
+        if node1 == node2 {
+            return depth1.cmp(&depth2);
+        }
+
+        // Climb to the children of the nearest common ancestor.
+        for bit in (0..self.nodes[node1].jumps.len()).rev() {
+            if let (Some(&jump1), Some(&jump2)) = (
+                self.nodes[node1].jumps.get(bit),
+                self.nodes[node2].jumps.get(bit),
+            ) {
+                if jump1 != jump2 {
+                    node1 = jump1;
+                    node2 = jump2;
+                }
+            }
+        }
+        self.nodes[node1].index.cmp(&self.nodes[node2].index)
+    }
+
+    /// Puts nodes in document order, without duplicates.
+    fn sort(&self, nodes: &mut Vec<NodeId>) {
+        nodes.sort_by(|node1, node2| self.document_order(*node1, *node2));
+        nodes.dedup();
+    }
+
+    /// The nodes along `axis` from `node`, nearest first.
+    fn axis(&mut self, axis: Axis, node: NodeId) -> UiacResult<Vec<NodeId>> {
+        let parent = self.nodes[node].parent;
+
+        Ok(match axis {
+            Axis::Child => self.children(node)?,
+            Axis::Descendant | Axis::DescendantOrSelf => {
+                let mut nodes = vec![];
+                if axis == Axis::DescendantOrSelf {
+                    nodes.push(node);
+                }
+                self.descendants(node, &mut nodes)?;
+                nodes
+            }
+            Axis::Self_ => vec![node],
+            Axis::Parent => parent.into_iter().collect(),
+            Axis::Ancestor | Axis::AncestorOrSelf => {
+                let mut nodes = vec![];
+                let mut ancestor = if axis == Axis::AncestorOrSelf {
+                    Some(node)
+                } else {
+                    parent
+                };
+                while let Some(node) = ancestor {
+                    nodes.push(node);
+                    ancestor = self.nodes[node].parent;
+                }
+                nodes
+            }
+            Axis::FollowingSibling | Axis::PrecedingSibling => {
+                let Some(parent) = parent else {
+                    return Ok(vec![]);
+                };
+                let index = self.nodes[node].index;
+                let siblings = self.children(parent)?;
+                if axis == Axis::FollowingSibling {
+                    siblings[index + 1..].to_vec()
+                } else {
+                    siblings[..index].iter().rev().copied().collect()
+                }
+            }
+        })
+    }
+
+    fn element(&self, node: NodeId) -> Option<&W::Element> {
+        self.nodes[node].element.as_ref()
+    }
+
+    fn test(&self, test: NodeTest, node: NodeId) -> UiacResult<bool> {
+        Ok(match (test, self.element(node)) {
+            (NodeTest::Node, _) => true,
+            (_, None) => false,
+            (NodeTest::Element, Some(_)) => true,
+            (NodeTest::ControlType(control_type), Some(element)) => {
+                element.get_current_control_type()? == control_type
+            }
+        })
+    }
+
+    fn attribute(&self, node: NodeId, property: Property) -> UiacResult<Option<String>> {
+        let element = match self.element(node) {
+            Some(element) => element,
+            None => return Ok(None),
+        };
+        Ok(attribute_text(
+            property,
+            &element.get_current_property_value(property)?,
+        ))
+    }
+
+    /// An element's string value is its name, as it has no text content.
+    fn string_value(&self, node: NodeId) -> UiacResult<String> {
+        Ok(self.attribute(node, Property::Name)?.unwrap_or_default())
+    }
+
+    fn eval(&mut self, expr: &Expr, context: &Context) -> UiacResult<Value> {
+        Ok(match expr {
+            Expr::Or(expr1, expr2) => {
+                Value::Bool(self.eval_bool(expr1, context)? || self.eval_bool(expr2, context)?)
+            }
+            Expr::And(expr1, expr2) => {
+                Value::Bool(self.eval_bool(expr1, context)? && self.eval_bool(expr2, context)?)
+            }
+            Expr::Compare(expr1, op, expr2) => {
+                let value1 = self.eval(expr1, context)?;
+                let value2 = self.eval(expr2, context)?;
+                Value::Bool(self.compare(value1, *op, value2)?)
+            }
+            Expr::Union(expr1, expr2) => {
+                match (self.eval(expr1, context)?, self.eval(expr2, context)?) {
+                    (Value::Nodes(mut nodes), Value::Nodes(nodes2)) => {
+                        nodes.extend(nodes2);
+                        self.sort(&mut nodes);
+                        Value::Nodes(nodes)
+                    }
+                    _ => {
+                        return Err(UiacError::Message(
+                            "\"|\" can only combine elements".to_owned(),
+                        ))
+                    }
+                }
+            }
+            Expr::Path(path) => self.eval_path(path, context)?,
+            Expr::Literal(s) => Value::String(s.clone()),
+            Expr::Number(n) => Value::Number(*n),
+            Expr::Function(function, args) => self.eval_function(*function, args, context)?,
+        })
+    }
+
+    fn eval_bool(&mut self, expr: &Expr, context: &Context) -> UiacResult<bool> {
+        let value = self.eval(expr, context)?;
+        Ok(to_bool(&value))
+    }
+
+    fn eval_string(&mut self, expr: &Expr, context: &Context) -> UiacResult<String> {
+        let value = self.eval(expr, context)?;
+        self.to_string(value)
+    }
+
+    fn eval_path(&mut self, path: &LocationPath, context: &Context) -> UiacResult<Value> {
+        let mut nodes = vec![if path.absolute { 0 } else { context.node }];
+
+        for step in &path.steps {
+            let mut next = vec![];
+            for node in &nodes {
+                let mut candidates = vec![];
+                for candidate in self.axis(step.axis, *node)? {
+                    if self.test(step.test, candidate)? {
+                        candidates.push(candidate);
+                    }
+                }
+                for predicate in &step.predicates {
+                    candidates = self.filter(candidates, predicate)?;
+                }
+                next.extend(candidates);
+            }
+            self.sort(&mut next);
+            nodes = next;
+        }
+
+        Ok(match path.attribute {
+            Some(property) => {
+                let mut values = vec![];
+                for node in &nodes {
+                    values.extend(self.attribute(*node, property)?);
+                }
+                Value::Attributes(values)
+            }
+            None => Value::Nodes(nodes),
+        })
+    }
+
+    fn filter(&mut self, nodes: Vec<NodeId>, predicate: &Expr) -> UiacResult<Vec<NodeId>> {
+        let size = nodes.len();
+        let mut kept = vec![];
+        for (index, node) in nodes.into_iter().enumerate() {
+            let context = Context {
+                node,
+                position: index + 1,
+                size,
+            };
+            let keep = match self.eval(predicate, &context)? {
+                Value::Number(n) => n == context.position as f64,
+                value => to_bool(&value),
+            };
+            if keep {
+                kept.push(context.node);
+            }
+        }
+        Ok(kept)
+    }
+
+    fn eval_function(
+        &mut self,
+        function: Function,
+        args: &[Expr],
+        context: &Context,
+    ) -> UiacResult<Value> {
+        // The functions that take an optional argument default to the context
+        // node.
+        let string_arg = |document: &mut Self| match args.first() {
+            Some(arg) => document.eval_string(arg, context),
+            None => document.string_value(context.node),
+        };
+
+        Ok(match function {
+            Function::True => Value::Bool(true),
+            Function::False => Value::Bool(false),
+            Function::Not => Value::Bool(!self.eval_bool(&args[0], context)?),
+            Function::Boolean => Value::Bool(self.eval_bool(&args[0], context)?),
+            Function::String => Value::String(string_arg(self)?),
+            Function::Number => {
+                let value = match args.first() {
+                    Some(arg) => self.eval(arg, context)?,
+                    None => Value::String(self.string_value(context.node)?),
+                };
+                Value::Number(self.to_number(value)?)
+            }
+            Function::Name => {
+                let node = match args.first() {
+                    Some(arg) => match self.eval(arg, context)? {
+                        Value::Nodes(nodes) => nodes.into_iter().next(),
+                        _ => return Err(UiacError::Message("name() takes elements".to_owned())),
+                    },
+                    None => Some(context.node),
+                };
+                Value::String(match node.and_then(|node| self.element(node)) {
+                    Some(element) => element.get_current_control_type()?.to_string(),
+                    None => String::new(),
+                })
+            }
+            Function::Position => Value::Number(context.position as f64),
+            Function::Last => Value::Number(context.size as f64),
+            Function::Count => match self.eval(&args[0], context)? {
+                Value::Nodes(nodes) => Value::Number(nodes.len() as f64),
+                Value::Attributes(values) => Value::Number(values.len() as f64),
+                _ => return Err(UiacError::Message("count() takes elements".to_owned())),
+            },
+            Function::Contains => {
+                let haystack = self.eval_string(&args[0], context)?;
+                let needle = self.eval_string(&args[1], context)?;
+                Value::Bool(haystack.contains(&needle))
+            }
+            Function::StartsWith => {
+                let s = self.eval_string(&args[0], context)?;
+                let prefix = self.eval_string(&args[1], context)?;
+                Value::Bool(s.starts_with(&prefix))
+            }
+            Function::StringLength => Value::Number(string_arg(self)?.chars().count() as f64),
+            Function::NormalizeSpace => Value::String(
+                string_arg(self)?
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ),
+            Function::Matches => {
+                let s = self.eval_string(&args[0], context)?;
+                let pattern = self.eval_string(&args[1], context)?;
+                let flags = match args.get(2) {
+                    Some(arg) => self.eval_string(arg, context)?,
+                    None => String::new(),
+                };
+                Value::Bool(self.matcher(pattern, flags)?.is_match(&s))
+            }
+        })
+    }
+
+    /// The matcher for a regex given to `matches()`, compiled the first time
+    /// it's needed.
+    fn matcher(&mut self, pattern: String, flags: String) -> UiacResult<&Matcher> {
+        let key = (pattern, flags);
+        if !self.matchers.contains_key(&key) {
+            let (pattern, flags) = &key;
+            let mut match_flags = MatchFlags::default();
+            for flag in flags.chars() {
+                match flag {
+                    'i' => match_flags.ignore_case = true,
+                    _ => {
+                        return Err(UiacError::Message(format!(
+                            "matches() doesn't support the flag {flag:?}"
+                        )))
+                    }
+                }
+            }
+            let matcher = Matcher::new(MatchKind::Regex, pattern.as_str(), match_flags)?;
+            self.matchers.insert(key.clone(), matcher);
+        }
+        Ok(&self.matchers[&key])
+    }
+
+    fn to_string(&self, value: Value) -> UiacResult<String> {
+        Ok(match value {
+            Value::Nodes(nodes) => match nodes.first() {
+                Some(node) => self.string_value(*node)?,
+                None => String::new(),
+            },
+            Value::Attributes(values) => values.into_iter().next().unwrap_or_default(),
+            Value::String(s) => s,
+            Value::Number(n) => number_to_string(n),
+            Value::Bool(b) => b.to_string(),
+        })
+    }
+
+    fn to_number(&self, value: Value) -> UiacResult<f64> {
+        Ok(match value {
+            Value::Number(n) => n,
+            Value::Bool(b) => f64::from(u8::from(b)),
+            value => string_to_number(&self.to_string(value)?),
+        })
+    }
+
+    /// Compares two values with XPath 1.0's rules, where a comparison with a
+    /// set is true if it holds for any member.
+    fn compare(&self, value1: Value, op: CompareOp, value2: Value) -> UiacResult<bool> {
+        match (self.members(value1)?, self.members(value2)?) {
+            (Ok(strings1), Ok(strings2)) => Ok(strings1.iter().any(|s1| {
+                strings2.iter().any(|s2| {
+                    compare_atoms(&Value::String(s1.clone()), op, &Value::String(s2.clone()))
+                })
+            })),
+            (Ok(strings), Err(atom)) => Ok(match atom {
+                Value::Bool(b) => {
+                    compare_atoms(&Value::Bool(!strings.is_empty()), op, &Value::Bool(b))
+                }
+                atom => strings
+                    .iter()
+                    .any(|s| compare_atoms(&Value::String(s.clone()), op, &atom)),
+            }),
+            (Err(atom), Ok(strings)) => Ok(match atom {
+                Value::Bool(b) => {
+                    compare_atoms(&Value::Bool(b), op, &Value::Bool(!strings.is_empty()))
+                }
+                atom => strings
+                    .iter()
+                    .any(|s| compare_atoms(&atom, op, &Value::String(s.clone()))),
+            }),
+            (Err(atom1), Err(atom2)) => Ok(compare_atoms(&atom1, op, &atom2)),
+        }
+    }
+
+    /// Splits a set into the string values of its members, passing other
+    /// values through.
+    fn members(&self, value: Value) -> UiacResult<Result<Vec<String>, Value>> {
+        Ok(match value {
+            Value::Nodes(nodes) => Ok(nodes
+                .iter()
+                .map(|node| self.string_value(*node))
+                .collect::<UiacResult<_>>()?),
+            Value::Attributes(values) => Ok(values),
+            value => Err(value),
+        })
+    }
+}
+
+fn to_bool(value: &Value) -> bool {
+    match value {
+        Value::Nodes(nodes) => !nodes.is_empty(),
+        Value::Attributes(values) => !values.is_empty(),
+        Value::String(s) => !s.is_empty(),
+        Value::Number(n) => *n != 0.0 && !n.is_nan(),
+        Value::Bool(b) => *b,
+    }
+}
+
+fn atom_to_number(value: &Value) -> f64 {
+    match value {
+        Value::Number(n) => *n,
+        Value::Bool(b) => f64::from(u8::from(*b)),
+        Value::String(s) => string_to_number(s),
+        _ => f64::NAN,
+    }
+}
+
+fn atom_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => number_to_string(*n),
+        Value::Bool(b) => b.to_string(),
+        _ => String::new(),
+    }
+}
+
+fn compare_atoms(value1: &Value, op: CompareOp, value2: &Value) -> bool {
+    match op {
+        CompareOp::Eq | CompareOp::NotEq => {
+            let equal = match (value1, value2) {
+                (Value::Bool(_), _) | (_, Value::Bool(_)) => to_bool(value1) == to_bool(value2),
+                (Value::Number(_), _) | (_, Value::Number(_)) => {
+                    atom_to_number(value1) == atom_to_number(value2)
+                }
+                _ => atom_to_string(value1) == atom_to_string(value2),
+            };
+            equal == (op == CompareOp::Eq)
+        }
+        CompareOp::Less => atom_to_number(value1) < atom_to_number(value2),
+        CompareOp::LessEq => atom_to_number(value1) <= atom_to_number(value2),
+        CompareOp::Greater => atom_to_number(value1) > atom_to_number(value2),
+        CompareOp::GreaterEq => atom_to_number(value1) >= atom_to_number(value2),
+    }
+}
+
+fn string_to_number(s: &str) -> f64 {
+    s.trim().parse().unwrap_or(f64::NAN)
+}
+
+fn number_to_string(n: f64) -> String {
+    if n.is_nan() {
+        "NaN".to_owned()
+    } else if n.is_infinite() {
+        if n > 0.0 { "Infinity" } else { "-Infinity" }.to_owned()
+    } else if n.fract() == 0.0 {
+        format!("{}", n as i64)
+    } else {
+        n.to_string()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Slash,
+    DoubleSlash,
+    OpenParen,
+    CloseParen,
+    OpenBracket,
+    CloseBracket,
+    Dot,
+    DotDot,
+    At,
+    Comma,
+    Pipe,
+    Star,
+    ColonColon,
+    Compare(CompareOp),
+    Name(String),
+    Literal(String),
+    Number(f64),
+}
+
+/// Splits an expression into tokens, each paired with its byte offset.
+fn tokenize(s: &str) -> UiacResult<Vec<(usize, Token)>> {
+    let error = |position, message: &str| UiacError::InvalidXPath {
+        xpath: s.to_owned(),
+        position,
+        message: message.to_owned(),
+    };
+
+    let mut tokens = vec![];
+    let mut chars = s.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let next = chars.peek().map(|(_, c)| *c);
+        let mut eat_next = || {
+            chars.next();
+        };
+
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '/' if next == Some('/') => {
+                eat_next();
+                Token::DoubleSlash
+            }
+            '/' => Token::Slash,
+            '(' => Token::OpenParen,
+            ')' => Token::CloseParen,
+            '[' => Token::OpenBracket,
+            ']' => Token::CloseBracket,
+            '@' => Token::At,
+            ',' => Token::Comma,
+            '|' => Token::Pipe,
+            '*' => Token::Star,
+            '=' => Token::Compare(CompareOp::Eq),
+            '!' if next == Some('=') => {
+                eat_next();
+                Token::Compare(CompareOp::NotEq)
+            }
+            '<' if next == Some('=') => {
+                eat_next();
+                Token::Compare(CompareOp::LessEq)
+            }
+            '<' => Token::Compare(CompareOp::Less),
+            '>' if next == Some('=') => {
+                eat_next();
+                Token::Compare(CompareOp::GreaterEq)
+            }
+            '>' => Token::Compare(CompareOp::Greater),
+            ':' if next == Some(':') => {
+                eat_next();
+                Token::ColonColon
+            }
+            '.' if next == Some('.') => {
+                eat_next();
+                Token::DotDot
+            }
+            '.' if !next.is_some_and(|c| c.is_ascii_digit()) => Token::Dot,
+            '"' | '\'' => {
+                let end = s[start + 1..]
+                    .find(c)
+                    .ok_or_else(|| error(start, "unterminated string"))?;
+                let literal = &s[start + 1..start + 1 + end];
+                while chars.next_if(|(i, _)| *i <= start + 1 + end).is_some() {}
+                Token::Literal(literal.to_owned())
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut end = start + c.len_utf8();
+                while let Some((i, c)) = chars.next_if(|(_, c)| c.is_ascii_digit() || *c == '.') {
+                    end = i + c.len_utf8();
+                }
+                Token::Number(
+                    s[start..end]
+                        .parse()
+                        .map_err(|_| error(start, "invalid number"))?,
+                )
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut end = start + c.len_utf8();
+                while let Some((i, c)) =
+                    chars.next_if(|(_, c)| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+                {
+                    end = i + c.len_utf8();
+                }
+                Token::Name(s[start..end].to_owned())
+            }
+            _ => return Err(error(start, &format!("unexpected {c:?}"))),
+        };
+        tokens.push((start, token));
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<(usize, Token)>,
+    index: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index).map(|(_, token)| token)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.index + offset).map(|(_, token)| token)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.index)
+            .map_or(self.source.len(), |(position, _)| *position)
+    }
+
+    fn error(&self, message: impl Into<String>) -> UiacError {
+        self.error_at(self.position(), message)
+    }
+
+    fn error_at(&self, position: usize, message: impl Into<String>) -> UiacError {
+        UiacError::InvalidXPath {
+            xpath: self.source.to_owned(),
+            position,
+            message: message.into(),
+        }
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &Token, description: &str) -> UiacResult<()> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected {description}")))
+        }
+    }
+
+    fn eat_operator(&mut self, name: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Name(n)) if n == name) {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_or(&mut self) -> UiacResult<Expr> {
+        let mut expr = self.parse_and()?;
+        while self.eat_operator("or") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> UiacResult<Expr> {
+        let mut expr = self.parse_comparison()?;
+        while self.eat_operator("and") {
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_comparison()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_comparison(&mut self) -> UiacResult<Expr> {
+        let mut expr = self.parse_union()?;
+        while let Some(Token::Compare(op)) = self.peek() {
+            let op = *op;
+            self.index += 1;
+            expr = Expr::Compare(Box::new(expr), op, Box::new(self.parse_union()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_union(&mut self) -> UiacResult<Expr> {
+        let mut expr = self.parse_primary()?;
+        while self.eat(&Token::Pipe) {
+            expr = Expr::Union(Box::new(expr), Box::new(self.parse_primary()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_primary(&mut self) -> UiacResult<Expr> {
+        match self.peek().cloned() {
+            Some(Token::Literal(s)) => {
+                self.index += 1;
+                Ok(Expr::Literal(s))
+            }
+            Some(Token::Number(n)) => {
+                self.index += 1;
+                Ok(Expr::Number(n))
+            }
+            Some(Token::OpenParen) => {
+                self.index += 1;
+                let expr = self.parse_or()?;
+                self.expect(&Token::CloseParen, "\")\"")?;
+                Ok(expr)
+            }
+            Some(Token::Name(name))
+                if self.peek_at(1) == Some(&Token::OpenParen)
+                    && name != "node"
+                    && name != "Unknown" =>
+            {
+                self.parse_function(&name)
+            }
+            _ => Ok(Expr::Path(self.parse_location_path()?)),
+        }
+    }
+
+    fn parse_function(&mut self, name: &str) -> UiacResult<Expr> {
+        let (_, function, min_args, max_args) = *Function::ALL
+            .iter()
+            .find(|(function_name, ..)| *function_name == name)
+            .ok_or_else(|| self.error(format!("unknown function {name:?}")))?;
+        let position = self.position();
+        self.index += 2;
+
+        let mut args = vec![];
+        if !self.eat(&Token::CloseParen) {
+            loop {
+                args.push(self.parse_or()?);
+                if self.eat(&Token::CloseParen) {
+                    break;
+                }
+                self.expect(&Token::Comma, "\",\" or \")\"")?;
+            }
+        }
+
+        if args.len() < min_args || args.len() > max_args {
+            return Err(self.error_at(
+                position,
+                format!(
+                    "{name}() takes {}",
+                    match (min_args, max_args) {
+                        (0, 0) => "no arguments".to_owned(),
+                        (min, max) if min == max => format!("{min} argument(s)"),
+                        (min, max) => format!("{min} to {max} arguments"),
+                    },
+                ),
+            ));
+        }
+
+        Ok(Expr::Function(function, args))
+    }
+
+    fn parse_location_path(&mut self) -> UiacResult<LocationPath> {
+        let mut path = LocationPath {
+            absolute: false,
+            steps: vec![],
+            attribute: None,
+        };
+
+        if self.eat(&Token::Slash) {
+            path.absolute = true;
+            // A lone "/" selects the document node.
+            if !self.starts_step() {
+                return Ok(path);
+            }
+        } else if self.eat(&Token::DoubleSlash) {
+            path.absolute = true;
+            path.steps.push(descendant_or_self());
+        }
+
+        loop {
+            if self.eat(&Token::At) {
+                path.attribute = Some(self.parse_attribute_name()?);
+                if matches!(self.peek(), Some(Token::Slash | Token::DoubleSlash)) {
+                    return Err(self.error("attributes have no children"));
+                }
+                return Ok(path);
+            }
+
+            path.steps.push(self.parse_step()?);
+
+            if self.eat(&Token::DoubleSlash) {
+                path.steps.push(descendant_or_self());
+            } else if !self.eat(&Token::Slash) {
+                return Ok(path);
+            }
+        }
+    }
+
+    fn starts_step(&self) -> bool {
+        matches!(
+            self.peek(),
+            Some(Token::Name(_) | Token::Star | Token::Dot | Token::DotDot | Token::At)
+        )
+    }
+
+    fn parse_attribute_name(&mut self) -> UiacResult<Property> {
+        match self.peek().cloned() {
+            Some(Token::Name(name)) => {
+                let property = name
+                    .parse()
+                    .map_err(|_| self.error(format!("unknown property {name:?}")))?;
+                self.index += 1;
+                Ok(property)
+            }
+            _ => Err(self.error("expected a property name")),
+        }
+    }
+
+    fn parse_step(&mut self) -> UiacResult<Step> {
+        if self.eat(&Token::Dot) {
+            return Ok(Step {
+                axis: Axis::Self_,
+                test: NodeTest::Node,
+                predicates: vec![],
+            });
+        }
+        if self.eat(&Token::DotDot) {
+            return Ok(Step {
+                axis: Axis::Parent,
+                test: NodeTest::Node,
+                predicates: vec![],
+            });
+        }
+
+        let axis = match (self.peek().cloned(), self.peek_at(1)) {
+            (Some(Token::Name(name)), Some(Token::ColonColon)) => {
+                let axis = match name.as_str() {
+                    "child" => Axis::Child,
+                    "descendant" => Axis::Descendant,
+                    "descendant-or-self" => Axis::DescendantOrSelf,
+                    "self" => Axis::Self_,
+                    "parent" => Axis::Parent,
+                    "ancestor" => Axis::Ancestor,
+                    "ancestor-or-self" => Axis::AncestorOrSelf,
+                    "following-sibling" => Axis::FollowingSibling,
+                    "preceding-sibling" => Axis::PrecedingSibling,
+                    _ => return Err(self.error(format!("unsupported axis {name:?}"))),
+                };
+                self.index += 2;
+                axis
+            }
+            _ => Axis::Child,
+        };
+
+        let test = match self.peek().cloned() {
+            Some(Token::Star) => NodeTest::Element,
+            Some(Token::Name(name)) if name == "node" => {
+                self.index += 1;
+                self.expect(&Token::OpenParen, "\"(\"")?;
+                if self.peek() != Some(&Token::CloseParen) {
+                    return Err(self.error("expected \")\""));
+                }
+                NodeTest::Node
+            }
+            // Control types without a name are written as they're printed, as
+            // in `Unknown(50100)`.
+            Some(Token::Name(name))
+                if name == "Unknown" && self.peek_at(1) == Some(&Token::OpenParen) =>
+            {
+                self.index += 2;
+                let id = match self.peek() {
+                    Some(&Token::Number(id)) if id.fract() == 0.0 && id >= 0.0 => id as u32,
+                    _ => return Err(self.error("expected a control type ID")),
+                };
+                self.index += 1;
+                if self.peek() != Some(&Token::CloseParen) {
+                    return Err(self.error("expected \")\""));
+                }
+                NodeTest::ControlType(ControlType::new(id))
+            }
+            Some(Token::Name(name)) => NodeTest::ControlType(
+                name.parse()
+                    .map_err(|_| self.error(format!("unknown control type {name:?}")))?,
+            ),
+            _ => return Err(self.error("expected a control type, \"*\" or \"node()\"")),
+        };
+        self.index += 1;
+
+        let mut predicates = vec![];
+        while self.eat(&Token::OpenBracket) {
+            predicates.push(self.parse_or()?);
+            self.expect(&Token::CloseBracket, "\"]\"")?;
+        }
+
+        Ok(Step {
+            axis,
+            test,
+            predicates,
+        })
+    }
+}
+
+fn descendant_or_self() -> Step {
+    Step {
+        axis: Axis::DescendantOrSelf,
+        test: NodeTest::Node,
+        predicates: vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xpath_error_test() {
+        let errors = [
+            ("", 0, "expected a control type"),
+            ("//Buton", 2, "unknown control type \"Buton\""),
+            ("//Button[@Nmae='OK']", 10, "unknown property \"Nmae\""),
+            ("//Button[@Name='OK'", 19, "expected \"]\""),
+            ("//Button[@Name='OK]", 15, "unterminated string"),
+            ("//Button[foo()]", 9, "unknown function \"foo\""),
+            (
+                "//Button[contains(@Name)]",
+                9,
+                "contains() takes 2 argument(s)",
+            ),
+            ("//Button/@Name/Text", 14, "attributes have no children"),
+            ("//following::Button", 2, "unsupported axis \"following\""),
+            ("//Button Button", 9, "expected the end of the expression"),
+            ("//Button[#]", 9, "unexpected '#'"),
+            ("//Unknown", 2, "unknown control type \"Unknown\""),
+            ("//Unknown(1.5)", 10, "expected a control type ID"),
+            ("//Unknown(5", 11, "expected \")\""),
+        ];
+
+        for (xpath, position, message) in errors {
+            match xpath.parse::<XPath>() {
+                Err(UiacError::InvalidXPath {
+                    position: actual_position,
+                    message: actual_message,
+                    ..
+                }) => {
+                    assert_eq!(actual_position, position, "{xpath:?}");
+                    assert!(
+                        actual_message.starts_with(message),
+                        "{xpath:?}: {actual_message}"
+                    );
+                }
+                result => panic!("{xpath:?} parsed as {result:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn number_to_string_test() {
+        assert_eq!(number_to_string(3.0), "3");
+        assert_eq!(number_to_string(-0.5), "-0.5");
+        assert_eq!(number_to_string(f64::NAN), "NaN");
+        assert_eq!(number_to_string(f64::INFINITY), "Infinity");
+    }
+}
@@ -0,0 +1,838 @@
+use {
+    std::{collections::BTreeMap, io},
+    uiac::{
+        backend::{Automation as _, Element as _},
+        dump,
+        memory::{Automation, Node},
+        resolve,
+        selector::Selector,
+        snapshot::Snapshot,
+        xpath::XPath,
+        ControlType, DumpOptions, Format, IndexPath, Order, Property, PropertyValue,
+        ResolveOptions, Root, UiacResult, View,
+    },
+};
+
+#[test]
+fn dump_text_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+
+    let mut out = vec![];
+    dump(&automation, &DumpOptions::default(), &mut out)?;
+
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        r#"│ Name = "Desktop 1", ControlType = Pane
+  │ Name = "Taskbar", ControlType = Pane
+    │ Name = "Start", ControlType = Button
+    │ Name = [no name], ControlType = Pane
+      │ Name = "Running applications", ControlType = ToolBar
+        │ Name = "Firefox", ControlType = Button
+        │ Name = [no name], ControlType = Custom
+    │ Name = "System Clock, 1:04 AM, \u{200e}1/\u{200e}22/\u{200e}2023", ControlType = Button
+    │ Name = "Show desktop", ControlType = Button
+  │ Name = [no name], ControlType = Window
+    │ Name = [no name], ControlType = TitleBar
+  │ Name = "Program Manager", ControlType = Pane
+    │ Name = "Desktop", ControlType = List
+      │ Name = "Recycle Bin", ControlType = ListItem
+"#
+    );
+
+    Ok(())
+}
+
+#[test]
+fn dump_json_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+
+    let mut out = vec![];
+    dump(
+        &automation,
+        &DumpOptions {
+            format: Format::Json,
+            properties: vec![Property::Name, Property::AutomationId],
+            ..DumpOptions::default()
+        },
+        &mut out,
+    )?;
+
+    let snapshot: serde_json::Value = serde_json::from_slice(&out)?;
+    assert_eq!(snapshot["uiac_version"], env!("CARGO_PKG_VERSION"));
+    assert!(snapshot["timestamp"].is_string());
+    assert_eq!(snapshot["root"], "desktop");
+
+    let tree = &snapshot["tree"];
+    assert_eq!(tree["name"], "Desktop 1");
+    assert_eq!(tree["control_type"], "Pane");
+    assert_eq!(tree["children"].as_array().unwrap().len(), 3);
+    assert_eq!(tree["children"][0]["children"][0]["name"], "Start");
+    assert_eq!(tree["children"][0]["children"][0]["control_type"], "Button");
+    assert_eq!(
+        tree["children"][0]["children"][0]["properties"],
+        serde_json::json!({"AutomationId": "StartButton"})
+    );
+    assert_eq!(
+        tree["children"][0]["properties"],
+        serde_json::json!({"AutomationId": {"reserved": "NotSupported"}})
+    );
+    assert_eq!(tree["children"][1]["name"], "");
+
+    Ok(())
+}
+
+#[test]
+fn dump_properties_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+
+    let mut out = vec![];
+    dump(
+        &automation,
+        &DumpOptions {
+            properties: vec![
+                Property::ControlType,
+                Property::AutomationId,
+                Property::IsEnabled,
+                Property::BoundingRectangle,
+            ],
+            ..DumpOptions::default()
+        },
+        &mut out,
+    )?;
+
+    let out = String::from_utf8(out).unwrap();
+    let lines: Vec<&str> = out.lines().take(3).collect();
+    assert_eq!(
+        lines,
+        [
+            "│ ControlType = Pane, AutomationId = [not supported], IsEnabled = [not supported], BoundingRectangle = [not supported]",
+            "  │ ControlType = Pane, AutomationId = [not supported], IsEnabled = [not supported], BoundingRectangle = [0.0, 1040.0, 1920.0, 40.0]",
+            "    │ ControlType = Button, AutomationId = \"StartButton\", IsEnabled = true, BoundingRectangle = [not supported]",
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn dump_max_depth_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+
+    let mut out = vec![];
+    dump(
+        &automation,
+        &DumpOptions {
+            max_depth: Some(1),
+            ..DumpOptions::default()
+        },
+        &mut out,
+    )?;
+
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        r#"│ Name = "Desktop 1", ControlType = Pane
+  │ Name = "Taskbar", ControlType = Pane
+  │ Name = [no name], ControlType = Window
+  │ Name = "Program Manager", ControlType = Pane
+"#
+    );
+
+    let mut out = vec![];
+    dump(
+        &automation,
+        &DumpOptions {
+            format: Format::Json,
+            max_depth: Some(0),
+            ..DumpOptions::default()
+        },
+        &mut out,
+    )?;
+
+    let snapshot: serde_json::Value = serde_json::from_slice(&out)?;
+    assert_eq!(snapshot["tree"]["children"], serde_json::json!([]));
+
+    Ok(())
+}
+
+#[test]
+fn dump_root_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+
+    let dump_root = |root: Root| -> UiacResult<String> {
+        let mut out = vec![];
+        dump(
+            &automation,
+            &DumpOptions {
+                root,
+                ..DumpOptions::default()
+            },
+            &mut out,
+        )?;
+        Ok(String::from_utf8(out).unwrap())
+    };
+
+    assert_eq!(
+        dump_root(Root::Window("ClassName=Progman".parse()?))?,
+        r#"│ Name = "Program Manager", ControlType = Pane
+  │ Name = "Desktop", ControlType = List
+    │ Name = "Recycle Bin", ControlType = ListItem
+"#
+    );
+
+    assert_eq!(
+        dump_root(Root::Focused)?,
+        "│ Name = \"Firefox\", ControlType = Button\n"
+    );
+
+    assert_eq!(
+        dump_root(Root::Query("AutomationId=StartButton".parse()?))?,
+        "│ Name = \"Start\", ControlType = Button\n"
+    );
+
+    // Roots can be picked out by any filter, including what UIA can't match.
+    assert_eq!(
+        dump_root(Root::Query(
+            r#"Name?="Show *" and ControlType=Button"#.parse()?
+        ))?,
+        "│ Name = \"Show desktop\", ControlType = Button\n"
+    );
+
+    // Only top-level windows are candidates for a window root.
+    let error = dump_root(Root::Window("Name=Start".parse()?)).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        r#"couldn't find the window matching Name="Start""#
+    );
+
+    Ok(())
+}
+
+#[test]
+fn dump_view_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+
+    let dump_view = |view: View| -> UiacResult<String> {
+        let mut out = vec![];
+        dump(
+            &automation,
+            &DumpOptions {
+                view,
+                ..DumpOptions::default()
+            },
+            &mut out,
+        )?;
+        Ok(String::from_utf8(out).unwrap())
+    };
+
+    assert_eq!(
+        dump_view(View::Control)?,
+        r#"│ Name = "Desktop 1", ControlType = Pane
+  │ Name = "Taskbar", ControlType = Pane
+    │ Name = "Start", ControlType = Button
+    │ Name = "Running applications", ControlType = ToolBar
+      │ Name = "Firefox", ControlType = Button
+      │ Name = [no name], ControlType = Custom
+    │ Name = "System Clock, 1:04 AM, \u{200e}1/\u{200e}22/\u{200e}2023", ControlType = Button
+    │ Name = "Show desktop", ControlType = Button
+  │ Name = [no name], ControlType = Window
+    │ Name = [no name], ControlType = TitleBar
+  │ Name = "Program Manager", ControlType = Pane
+    │ Name = "Desktop", ControlType = List
+      │ Name = "Recycle Bin", ControlType = ListItem
+"#
+    );
+
+    let content = dump_view(View::Content)?;
+    assert!(content
+        .contains("\n  │ Name = [no name], ControlType = Window\n  │ Name = \"Program Manager\""));
+
+    Ok(())
+}
+
+#[test]
+fn dump_condition_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+
+    let mut out = vec![];
+    dump(
+        &automation,
+        &DumpOptions {
+            condition: "ControlType=Button".parse()?,
+            ..DumpOptions::default()
+        },
+        &mut out,
+    )?;
+
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        r#"│ Name = "Desktop 1", ControlType = Pane
+  │ Name = "Start", ControlType = Button
+  │ Name = "Firefox", ControlType = Button
+  │ Name = "System Clock, 1:04 AM, \u{200e}1/\u{200e}22/\u{200e}2023", ControlType = Button
+  │ Name = "Show desktop", ControlType = Button
+"#
+    );
+
+    Ok(())
+}
+
+#[test]
+fn dump_matcher_condition_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+
+    let mut out = vec![];
+    dump(
+        &automation,
+        &DumpOptions {
+            condition: r#"ControlType=Button and not Name?="S* *""#.parse()?,
+            ..DumpOptions::default()
+        },
+        &mut out,
+    )?;
+
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        r#"│ Name = "Desktop 1", ControlType = Pane
+  │ Name = "Start", ControlType = Button
+  │ Name = "Firefox", ControlType = Button
+"#
+    );
+
+    let mut out = vec![];
+    dump(
+        &automation,
+        &DumpOptions {
+            format: Format::Json,
+            condition: "Name/=\"^(Taskbar|Start)$\"".parse()?,
+            ..DumpOptions::default()
+        },
+        &mut out,
+    )?;
+
+    let snapshot = Snapshot::from_json(std::str::from_utf8(&out).unwrap())?;
+    assert_eq!(snapshot.tree.children.len(), 1);
+    assert_eq!(snapshot.tree.children[0].name, "Taskbar");
+    assert_eq!(snapshot.tree.children[0].children[0].name, "Start");
+    assert!(snapshot.tree.children[0].children[0].children.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn dump_paths_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+
+    let mut out = vec![];
+    dump(
+        &automation,
+        &DumpOptions {
+            view: View::Control,
+            max_depth: Some(2),
+            paths: true,
+            ..DumpOptions::default()
+        },
+        &mut out,
+    )?;
+
+    // Paths count raw view positions, so the unnamed pane that the control
+    // view skips still takes up 0/0/1.
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        r#"│ 0: Name = "Desktop 1", ControlType = Pane
+  │ 0/0: Name = "Taskbar", ControlType = Pane
+    │ 0/0/0: Name = "Start", ControlType = Button
+    │ 0/0/1/0: Name = "Running applications", ControlType = ToolBar
+    │ 0/0/2: Name = "System Clock, 1:04 AM, \u{200e}1/\u{200e}22/\u{200e}2023", ControlType = Button
+    │ 0/0/3: Name = "Show desktop", ControlType = Button
+  │ 0/1: Name = [no name], ControlType = Window
+    │ 0/1/0: Name = [no name], ControlType = TitleBar
+  │ 0/2: Name = "Program Manager", ControlType = Pane
+    │ 0/2/0: Name = "Desktop", ControlType = List
+"#
+    );
+
+    let mut out = vec![];
+    dump(
+        &automation,
+        &DumpOptions {
+            format: Format::Json,
+            root: Root::Path("0/0/1".parse()?),
+            paths: true,
+            ..DumpOptions::default()
+        },
+        &mut out,
+    )?;
+
+    let snapshot = Snapshot::from_json(std::str::from_utf8(&out).unwrap())?;
+    assert_eq!(snapshot.root, "element at 0/0/1");
+    assert_eq!(snapshot.tree.path, Some("0/0/1".parse()?));
+    assert_eq!(
+        snapshot.tree.children[0].children[1].path,
+        Some("0/0/1/0/1".parse()?)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn resolve_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+
+    let mut out = vec![];
+    resolve(
+        &automation,
+        &ResolveOptions {
+            path: "0/0/0".parse()?,
+            ..ResolveOptions::default()
+        },
+        &mut out,
+    )?;
+
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        r#"0/0/0
+  ControlType = Button
+  Name = "Start"
+  IsEnabled = true
+  AutomationId = "StartButton"
+  IsControlElement = true
+  IsContentElement = true
+"#
+    );
+
+    let err = resolve(
+        &automation,
+        &ResolveOptions {
+            path: "0/0/9".parse()?,
+            ..ResolveOptions::default()
+        },
+        &mut vec![],
+    )
+    .unwrap_err();
+    assert_eq!(err.to_string(), "there's no element at 0/0/9");
+
+    Ok(())
+}
+
+#[test]
+fn dump_order_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+
+    let mut out = vec![];
+    dump(
+        &automation,
+        &DumpOptions {
+            view: View::Control,
+            order: Order::BreadthFirst,
+            ..DumpOptions::default()
+        },
+        &mut out,
+    )?;
+
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        r#"│ Name = "Desktop 1", ControlType = Pane
+  │ Name = "Taskbar", ControlType = Pane
+  │ Name = [no name], ControlType = Window
+  │ Name = "Program Manager", ControlType = Pane
+    │ Name = "Start", ControlType = Button
+    │ Name = "Running applications", ControlType = ToolBar
+    │ Name = "System Clock, 1:04 AM, \u{200e}1/\u{200e}22/\u{200e}2023", ControlType = Button
+    │ Name = "Show desktop", ControlType = Button
+    │ Name = [no name], ControlType = TitleBar
+    │ Name = "Desktop", ControlType = List
+      │ Name = "Firefox", ControlType = Button
+      │ Name = [no name], ControlType = Custom
+      │ Name = "Recycle Bin", ControlType = ListItem
+"#
+    );
+
+    Ok(())
+}
+
+/// Counts a byte rather than keeping what's written, as a deep tree's
+/// indentation adds up.
+struct ByteCounter {
+    byte: u8,
+    count: usize,
+}
+
+impl ByteCounter {
+    fn new(byte: u8) -> Self {
+        Self { byte, count: 0 }
+    }
+}
+
+impl io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.count += buf.iter().filter(|b| **b == self.byte).count();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn dump_deep_tree_test() -> UiacResult<()> {
+    // Deep enough to overflow a test thread's stack if walked by recursion.
+    const DEPTH: usize = 10_000;
+
+    let mut node = Node {
+        name: "Leaf".to_owned(),
+        control_type: ControlType::Text,
+        path: None,
+        properties: BTreeMap::new(),
+        children: vec![],
+    };
+    for _ in 0..DEPTH {
+        node = Node {
+            name: String::new(),
+            control_type: ControlType::Group,
+            path: None,
+            properties: BTreeMap::new(),
+            children: vec![node],
+        };
+    }
+    let automation = Automation::new(node);
+
+    for order in [Order::DepthFirst, Order::BreadthFirst] {
+        let mut out = ByteCounter::new(b'\n');
+        dump(
+            &automation,
+            &DumpOptions {
+                order,
+                ..DumpOptions::default()
+            },
+            &mut out,
+        )?;
+        assert_eq!(out.count, DEPTH + 1);
+    }
+
+    // Pretty JSON's indentation grows with depth, so it's cut short, though
+    // still deeper than serializing by recursion could manage. Every node has
+    // a children array, and nothing else has brackets.
+    let mut out = ByteCounter::new(b'[');
+    dump(
+        &automation,
+        &DumpOptions {
+            format: Format::Json,
+            max_depth: Some(DEPTH / 4),
+            ..DumpOptions::default()
+        },
+        &mut out,
+    )?;
+    assert_eq!(out.count, DEPTH / 4 + 1);
+
+    let mut out = vec![];
+    dump(
+        &automation,
+        &DumpOptions {
+            format: Format::Html,
+            ..DumpOptions::default()
+        },
+        &mut out,
+    )?;
+    let html = String::from_utf8(out).unwrap();
+    assert_eq!(html.matches(r#""children":"#).count(), DEPTH + 1);
+
+    // Finding the leaf by selector, XPath and index path goes just as deep.
+    let walker = automation.create_tree_walker(&automation.get_raw_view_condition()?)?;
+    let desktop = automation.get_root_element()?;
+    let leaves = "Text".parse::<Selector>()?.find_all(&walker, &desktop)?;
+    assert_eq!(leaves.len(), 1);
+    let leaf = &leaves[0];
+    assert_eq!(leaf.get_current_name()?, "Leaf");
+
+    let found = "//Text".parse::<XPath>()?.find_all(&walker, &desktop)?;
+    assert_eq!(found.len(), 1);
+    assert!(automation.compare_elements(&found[0], leaf)?);
+
+    let path = IndexPath::of(&automation, leaf)?;
+    assert_eq!(path, IndexPath(vec![0; DEPTH]));
+    assert!(automation.compare_elements(&path.resolve(&walker, &desktop)?, leaf)?);
+
+    Ok(())
+}
+
+#[test]
+fn dump_xml_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+
+    let mut out = vec![];
+    dump(
+        &automation,
+        &DumpOptions {
+            format: Format::Xml,
+            root: Root::Window("Name=Taskbar".parse()?),
+            view: View::Control,
+            ..DumpOptions::default()
+        },
+        &mut out,
+    )?;
+
+    let xml = String::from_utf8(out).unwrap();
+    let lines: Vec<&str> = xml.lines().collect();
+    assert_eq!(lines.len(), 10, "{xml}");
+    assert_eq!(lines[0], r#"<?xml version="1.0" encoding="utf-8"?>"#);
+
+    // Positions are relative to the root, and come after WinAppDriver's other
+    // attributes.
+    assert!(lines[1].starts_with(
+        r#"<Pane AcceleratorKey="" AccessKey="" AutomationId="" ClassName="Shell_TrayWnd" "#
+    ));
+    assert!(lines[1].ends_with(
+        r#" Name="Taskbar" Orientation="" ProcessId="" RuntimeId="" x="0" y="0" width="1920" height="40">"#
+    ));
+
+    assert!(lines[2]
+        .starts_with(r#"  <Button AcceleratorKey="" AccessKey="" AutomationId="StartButton" "#));
+    assert!(lines[2].contains(r#" IsEnabled="True" "#));
+    assert!(lines[2].ends_with(r#" RuntimeId="" />"#));
+    assert!(lines[3].starts_with("  <ToolBar "));
+    assert!(lines[3].ends_with(r#" RuntimeId="">"#));
+    assert!(lines[4].starts_with("    <Button "));
+    assert!(lines[4].contains(r#" HasKeyboardFocus="True" "#));
+    assert!(lines[5].starts_with("    <Custom "));
+    assert_eq!(lines[6], "  </ToolBar>");
+    assert!(lines[7].contains(r#" Name="System Clock, 1:04 AM, ‎1/‎22/‎2023" "#));
+    assert!(lines[8].contains(r#" Name="Show desktop" "#));
+    assert_eq!(lines[9], "</Pane>");
+
+    Ok(())
+}
+
+#[test]
+fn dump_dot_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+
+    let mut out = vec![];
+    dump(
+        &automation,
+        &DumpOptions {
+            format: Format::Dot,
+            max_depth: Some(2),
+            view: View::Control,
+            condition: "ControlType=Pane or ControlType=Button".parse()?,
+            color_by_control_type: true,
+            ..DumpOptions::default()
+        },
+        &mut out,
+    )?;
+
+    // Each of the desktop's children, the top-level windows, gets a cluster.
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        r#"digraph uiac {
+  rankdir=LR;
+  node [shape=box, fontname="Segoe UI"];
+  n0 [label="Pane\n\"Desktop 1\"", style=filled, fillcolor="0.095 0.350 0.950"];
+  subgraph cluster_0 {
+    label="Pane\n\"Taskbar\"";
+    n1 [label="Pane\n\"Taskbar\"", style=filled, fillcolor="0.095 0.350 0.950"];
+    n2 [label="Button\n\"Start\"", style=filled, fillcolor="0.699 0.350 0.950"];
+    n3 [label="Button\n\"Firefox\"", style=filled, fillcolor="0.699 0.350 0.950"];
+    n4 [label="Button\n\"System Clock, 1:04 AM, ‎1/‎22/‎2023\"", style=filled, fillcolor="0.699 0.350 0.950"];
+    n5 [label="Button\n\"Show desktop\"", style=filled, fillcolor="0.699 0.350 0.950"];
+  }
+  subgraph cluster_1 {
+    label="Pane\n\"Program Manager\"";
+    n6 [label="Pane\n\"Program Manager\"", style=filled, fillcolor="0.095 0.350 0.950"];
+  }
+  n0 -> n1;
+  n1 -> n2;
+  n1 -> n3;
+  n1 -> n4;
+  n1 -> n5;
+  n0 -> n6;
+}
+"#
+    );
+
+    Ok(())
+}
+
+#[test]
+fn dump_html_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+
+    let mut out = vec![];
+    dump(
+        &automation,
+        &DumpOptions {
+            format: Format::Html,
+            properties: vec![
+                Property::Name,
+                Property::ControlType,
+                Property::AutomationId,
+            ],
+            paths: true,
+            ..DumpOptions::default()
+        },
+        &mut out,
+    )?;
+
+    let html = String::from_utf8(out).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    // Everything's inline, so the page opens offline.
+    assert!(!html.contains(" src="));
+    assert!(!html.contains(" href="));
+
+    // The snapshot is embedded whole.
+    let start = r#"<script id="snapshot" type="application/json">"#;
+    let json = &html[html.find(start).unwrap() + start.len()..];
+    let snapshot = Snapshot::from_json(&json[..json.find("</script>").unwrap()])?;
+    assert_eq!(snapshot.root, "desktop");
+    assert_eq!(snapshot.tree.name, "Desktop 1");
+    assert_eq!(snapshot.tree.path.as_ref().unwrap().to_string(), "0");
+    let taskbar = &snapshot.tree.children[0];
+    assert_eq!(taskbar.name, "Taskbar");
+    assert_eq!(
+        taskbar.children[0].properties[&Property::AutomationId],
+        PropertyValue::String("StartButton".to_owned())
+    );
+
+    Ok(())
+}
+
+#[test]
+fn dump_table_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+    let options = DumpOptions {
+        format: Format::Csv,
+        properties: vec![
+            Property::ControlType,
+            Property::Name,
+            Property::AutomationId,
+            Property::IsEnabled,
+        ],
+        root: Root::Window("Name=Taskbar".parse()?),
+        view: View::Control,
+        ..DumpOptions::default()
+    };
+
+    // Paths are there without asking, and a parent path can be of an element
+    // the view leaves out, like the ToolBar's.
+    let mut out = vec![];
+    dump(&automation, &options, &mut out)?;
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "Depth,IndexPath,ParentPath,ControlType,Name,AutomationId,IsEnabled\r\n\
+         0,0/0,0,Pane,Taskbar,,\r\n\
+         1,0/0/0,0/0,Button,Start,StartButton,True\r\n\
+         1,0/0/1/0,0/0/1,ToolBar,Running applications,,\r\n\
+         2,0/0/1/0/0,0/0/1/0,Button,Firefox,,\r\n\
+         2,0/0/1/0/1,0/0/1/0,Custom,,,\r\n\
+         1,0/0/2,0/0,Button,\"System Clock, 1:04 AM, \u{200e}1/\u{200e}22/\u{200e}2023\",,\r\n\
+         1,0/0/3,0/0,Button,Show desktop,,\r\n"
+    );
+
+    let mut out = vec![];
+    dump(
+        &automation,
+        &DumpOptions {
+            format: Format::Tsv,
+            order: Order::BreadthFirst,
+            max_depth: Some(1),
+            ..options
+        },
+        &mut out,
+    )?;
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "Depth\tIndexPath\tParentPath\tControlType\tName\tAutomationId\tIsEnabled\n\
+         0\t0/0\t0\tPane\tTaskbar\t\t\n\
+         1\t0/0/0\t0/0\tButton\tStart\tStartButton\tTrue\n\
+         1\t0/0/1/0\t0/0/1\tToolBar\tRunning applications\t\t\n\
+         1\t0/0/2\t0/0\tButton\tSystem Clock, 1:04 AM, \u{200e}1/\u{200e}22/\u{200e}2023\t\t\n\
+         1\t0/0/3\t0/0\tButton\tShow desktop\t\t\n"
+    );
+
+    Ok(())
+}
+
+/// Records what had been written each time it's flushed.
+#[derive(Default)]
+struct Flushes {
+    written: Vec<u8>,
+    flushed: Vec<String>,
+}
+
+impl io::Write for Flushes {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.written.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.flushed
+            .push(String::from_utf8(self.written.clone()).unwrap());
+        Ok(())
+    }
+}
+
+#[test]
+fn dump_ndjson_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+
+    let mut out = Flushes::default();
+    dump(
+        &automation,
+        &DumpOptions {
+            format: Format::Ndjson,
+            properties: vec![
+                Property::Name,
+                Property::ControlType,
+                Property::AutomationId,
+            ],
+            max_depth: Some(2),
+            view: View::Control,
+            ..DumpOptions::default()
+        },
+        &mut out,
+    )?;
+
+    let ndjson = String::from_utf8(out.written).unwrap();
+    let lines: Vec<serde_json::Value> = ndjson
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    assert_eq!(lines.len(), 10, "{ndjson}");
+    assert_eq!(
+        lines[0],
+        serde_json::json!({
+            "depth": 0,
+            "path": "0",
+            "parent_path": null,
+            "name": "Desktop 1",
+            "control_type": "Pane",
+            "properties": {"AutomationId": {"reserved": "NotSupported"}},
+        })
+    );
+    assert_eq!(
+        lines[2],
+        serde_json::json!({
+            "depth": 2,
+            "path": "0/0/0",
+            "parent_path": "0/0",
+            "name": "Start",
+            "control_type": "Button",
+            "properties": {"AutomationId": "StartButton"},
+        })
+    );
+    // The control view leaves out the ToolBar's parent, so its depth and path
+    // disagree.
+    assert_eq!(lines[3]["depth"], 2);
+    assert_eq!(lines[3]["path"], "0/0/1/0");
+    assert_eq!(lines[3]["parent_path"], "0/0/1");
+
+    // Each line is flushed as soon as it's written.
+    assert_eq!(out.flushed.len(), lines.len());
+    for (count, flushed) in out.flushed.iter().enumerate() {
+        assert_eq!(flushed.lines().count(), count + 1);
+        assert!(flushed.ends_with('\n'));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,258 @@
+use uiac::{
+    find, memory::Automation, snapshot::Snapshot, FindOptions, Format, Property, PropertyValue,
+    Root, TreeScope, UiacResult, View,
+};
+
+fn find_to_string(automation: &Automation, options: &FindOptions) -> UiacResult<String> {
+    let mut out = vec![];
+    find(automation, options, &mut out)?;
+    Ok(String::from_utf8(out).unwrap())
+}
+
+#[test]
+fn find_all_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+
+    assert_eq!(
+        find_to_string(
+            &automation,
+            &FindOptions {
+                condition: "ControlType=Button and Name*~=S".parse()?,
+                ..FindOptions::default()
+            },
+        )?,
+        r#"│ Name = "Start", ControlType = Button
+│ Name = "System Clock, 1:04 AM, \u{200e}1/\u{200e}22/\u{200e}2023", ControlType = Button
+│ Name = "Show desktop", ControlType = Button
+"#
+    );
+
+    assert_eq!(
+        find_to_string(
+            &automation,
+            &FindOptions {
+                condition: "ControlType=Button".parse()?,
+                count: true,
+                ..FindOptions::default()
+            },
+        )?,
+        "4\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn find_scope_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+
+    let count = |scope: TreeScope| {
+        find_to_string(
+            &automation,
+            &FindOptions {
+                root: Root::Window("Name=Taskbar".parse()?),
+                scope,
+                count: true,
+                ..FindOptions::default()
+            },
+        )
+    };
+
+    assert_eq!(count(TreeScope::Children)?, "4\n");
+    assert_eq!(count(TreeScope::Descendants)?, "7\n");
+    assert_eq!(count(TreeScope::Subtree)?, "8\n");
+
+    Ok(())
+}
+
+#[test]
+fn find_first_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+
+    assert_eq!(
+        find_to_string(
+            &automation,
+            &FindOptions {
+                condition: "ControlType=ToolBar".parse()?,
+                first: true,
+                max_depth: Some(1),
+                ..FindOptions::default()
+            },
+        )?,
+        r#"│ Name = "Running applications", ControlType = ToolBar
+  │ Name = "Firefox", ControlType = Button
+  │ Name = [no name], ControlType = Custom
+"#
+    );
+
+    let error = find_to_string(
+        &automation,
+        &FindOptions {
+            condition: "Name=Nothing".parse()?,
+            first: true,
+            ..FindOptions::default()
+        },
+    )
+    .unwrap_err();
+    assert_eq!(error.to_string(), r#"no element matches Name="Nothing""#);
+
+    Ok(())
+}
+
+#[test]
+fn find_matcher_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+
+    let find_names = |condition: &str, first| {
+        find_to_string(
+            &automation,
+            &FindOptions {
+                condition: condition.parse()?,
+                first,
+                properties: vec![Property::Name],
+                ..FindOptions::default()
+            },
+        )
+    };
+
+    assert_eq!(
+        find_names("Name^~=s and ControlType=Button", false)?,
+        r#"│ Name = "Start"
+│ Name = "System Clock, 1:04 AM, \u{200e}1/\u{200e}22/\u{200e}2023"
+│ Name = "Show desktop"
+"#
+    );
+    assert_eq!(
+        find_names("ClassName/=^Shell_", false)?,
+        "│ Name = \"Taskbar\"\n"
+    );
+    assert_eq!(
+        find_names("Name?=\"* *\" and not Name$=desktop", true)?,
+        "│ Name = \"Running applications\"\n"
+    );
+    assert_eq!(
+        find_names("Name~_=\" show   DESKTOP\"", false)?,
+        "│ Name = \"Show desktop\"\n"
+    );
+    assert_eq!(
+        find_names("ControlType?=*Item or ControlType$=Bar", false)?,
+        r#"│ Name = "Running applications"
+│ Name = [no name]
+│ Name = "Recycle Bin"
+"#
+    );
+
+    Ok(())
+}
+
+#[test]
+fn find_json_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+
+    let out = find_to_string(
+        &automation,
+        &FindOptions {
+            format: Format::Json,
+            condition: "ControlType=ListItem or ControlType=TitleBar".parse()?,
+            ..FindOptions::default()
+        },
+    )?;
+
+    let matches: serde_json::Value = serde_json::from_str(&out)?;
+    assert_eq!(
+        matches,
+        serde_json::json!([
+            {"name": "", "control_type": "TitleBar", "properties": {}, "children": []},
+            {"name": "Recycle Bin", "control_type": "ListItem", "properties": {}, "children": []},
+        ])
+    );
+
+    Ok(())
+}
+
+#[test]
+fn find_table_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+    let options = FindOptions {
+        format: Format::Csv,
+        properties: vec![Property::Name, Property::AutomationId],
+        condition: "ControlType=Button".parse()?,
+        view: View::Control,
+        ..FindOptions::default()
+    };
+
+    // One header, then a row for each match, with its index path.
+    assert_eq!(
+        find_to_string(&automation, &options)?,
+        "Depth,IndexPath,ParentPath,ControlType,Name,AutomationId\r\n\
+         0,0/0/0,0/0,Button,Start,StartButton\r\n\
+         0,0/0/1/0/0,0/0/1/0,Button,Firefox,\r\n\
+         0,0/0/2,0/0,Button,\"System Clock, 1:04 AM, \u{200e}1/\u{200e}22/\u{200e}2023\",\r\n\
+         0,0/0/3,0/0,Button,Show desktop,\r\n"
+    );
+
+    // Subtrees of matches follow their rows, in the view being searched.
+    assert_eq!(
+        find_to_string(
+            &automation,
+            &FindOptions {
+                format: Format::Tsv,
+                condition: "Name=Taskbar".parse()?,
+                max_depth: Some(1),
+                ..options
+            },
+        )?,
+        "Depth\tIndexPath\tParentPath\tControlType\tName\tAutomationId\n\
+         0\t0/0\t0\tPane\tTaskbar\t\n\
+         1\t0/0/0\t0/0\tButton\tStart\tStartButton\n\
+         1\t0/0/1/0\t0/0/1\tToolBar\tRunning applications\t\n\
+         1\t0/0/2\t0/0\tButton\tSystem Clock, 1:04 AM, \u{200e}1/\u{200e}22/\u{200e}2023\t\n\
+         1\t0/0/3\t0/0\tButton\tShow desktop\t\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn find_html_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+    let html = find_to_string(
+        &automation,
+        &FindOptions {
+            format: Format::Html,
+            properties: vec![Property::Name, Property::AutomationId],
+            condition: "ControlType=Button".parse()?,
+            view: View::Control,
+            ..FindOptions::default()
+        },
+    )?;
+    assert!(html.starts_with("<!DOCTYPE html>"));
+
+    // The matches hang from the root they were found under, with their paths.
+    let start = r#"<script id="snapshot" type="application/json">"#;
+    let json = &html[html.find(start).unwrap() + start.len()..];
+    let snapshot = Snapshot::from_json(&json[..json.find("</script>").unwrap()])?;
+    assert_eq!(snapshot.root, "desktop");
+    assert_eq!(snapshot.tree.name, "Desktop 1");
+    assert_eq!(snapshot.tree.path.as_ref().unwrap().to_string(), "0");
+    let matches: Vec<_> = snapshot
+        .tree
+        .children
+        .iter()
+        .map(|node| (node.name.as_str(), node.path.as_ref().unwrap().to_string()))
+        .collect();
+    assert_eq!(
+        matches[..2],
+        [
+            ("Start", "0/0/0".to_owned()),
+            ("Firefox", "0/0/1/0/0".to_owned())
+        ]
+    );
+    assert_eq!(matches.len(), 4);
+    assert_eq!(
+        snapshot.tree.children[0].properties[&Property::AutomationId],
+        PropertyValue::String("StartButton".to_owned())
+    );
+
+    Ok(())
+}
@@ -0,0 +1,206 @@
+use uiac::{
+    backend::{Automation as _, Element as _, TreeWalker as _},
+    find, locate,
+    memory::Automation,
+    selector::Selector,
+    FindOptions, Format, IndexPath, UiacResult,
+};
+
+/// Locates the element at `path` and checks that the locator finds it alone.
+fn locate_path(automation: &Automation, path: &str) -> UiacResult<String> {
+    let walker = automation.create_tree_walker(&automation.get_raw_view_condition()?)?;
+    let desktop = automation.get_root_element()?;
+    let path: IndexPath = path.parse()?;
+
+    let locator = locate(&walker, &desktop, &path)?.to_string();
+
+    let selector: Selector = locator.parse()?;
+    let found = selector.find_all(&walker, &desktop)?;
+    assert_eq!(found.len(), 1, "{locator} should match one element");
+    assert!(
+        automation.compare_elements(&found[0], &path.resolve(&walker, &desktop)?)?,
+        "{locator} should match {path}"
+    );
+
+    Ok(locator)
+}
+
+#[test]
+fn locate_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+
+    assert_eq!(
+        locate_path(&automation, "0/0/0")?,
+        r#"> Pane[Name="Taskbar"] Button[AutomationId="StartButton"]"#
+    );
+    assert_eq!(
+        locate_path(&automation, "0/0/1/0/0")?,
+        r#"> Pane[Name="Taskbar"] Button[Name="Firefox"]"#
+    );
+    assert_eq!(
+        locate_path(&automation, "0/0/1/0/1")?,
+        r#"> Pane[Name="Taskbar"] Custom"#
+    );
+    assert_eq!(locate_path(&automation, "0/1/0")?, "> Window TitleBar");
+    assert_eq!(
+        locate_path(&automation, "0/2")?,
+        r#"> Pane[Name="Program Manager"]"#
+    );
+
+    assert!(locate_path(&automation, "0").is_err());
+    assert!(locate_path(&automation, "0/9").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn locate_find_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+    let walker = automation.create_tree_walker(&automation.get_raw_view_condition()?)?;
+    let desktop = automation.get_root_element()?;
+
+    // The clock's name has left-to-right marks in it, which are written as
+    // they are.
+    let clock = "System Clock, 1:04 AM, \u{200e}1/\u{200e}22/\u{200e}2023";
+    assert_eq!(
+        locate_path(&automation, "0/0/2")?,
+        format!(r#"> Pane[Name="Taskbar"] Button[Name="{clock}"]"#)
+    );
+
+    // What `locate` prints, `find --selector` finds, and only that.
+    for element in walker.descendants(&desktop) {
+        let element = element?;
+        let path = IndexPath::of(&automation, &element)?;
+        let locator = locate_path(&automation, &path.to_string())?;
+        let mut out = vec![];
+        find(
+            &automation,
+            &FindOptions {
+                format: Format::Ndjson,
+                selector: Some(locator.parse()?),
+                ..FindOptions::default()
+            },
+            &mut out,
+        )?;
+        let found: serde_json::Value = serde_json::from_slice(&out)?;
+        assert_eq!(found["name"], element.get_current_name()?, "{locator}");
+        assert_eq!(
+            found["control_type"],
+            element.get_current_control_type()?.to_string(),
+            "{locator}"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn locate_unknown_control_type_test() -> UiacResult<()> {
+    let automation = Automation::from_json(
+        r#"{
+            "name": "Desktop 1",
+            "control_type": "Pane",
+            "children": [
+                {
+                    "name": "W",
+                    "control_type": "Window",
+                    "children": [
+                        {"name": "x", "control_type": "Unknown(50100)"},
+                        {"name": "y", "control_type": "Unknown(50100)"},
+                        {"control_type": "Unknown(50101)"}
+                    ]
+                }
+            ]
+        }"#,
+    )?;
+
+    assert_eq!(
+        locate_path(&automation, "0/0/1")?,
+        r#"> Window[Name="W"] Unknown(50100)[Name="y"]"#
+    );
+    assert_eq!(
+        locate_path(&automation, "0/0/2")?,
+        r#"> Window[Name="W"] Unknown(50101)"#
+    );
+
+    Ok(())
+}
+
+#[test]
+fn locate_anchor_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/calculator.json")?;
+
+    assert_eq!(
+        locate_path(&automation, "0/0/0/0")?,
+        r#"> Window[Name="Calculator"]:nth(1) Button[AutomationId="num1Button"]"#
+    );
+    // "Two" isn't unique in the window, but is in the number pad.
+    assert_eq!(
+        locate_path(&automation, "0/0/0/1")?,
+        r#"> Window[Name="Calculator"]:nth(1) Group[AutomationId="NumberPad"] Button[Name="Two"]"#
+    );
+    assert_eq!(
+        locate_path(&automation, "0/0/1/1")?,
+        r#"> Window[Name="Calculator"]:nth(1) Button[Name="Clear"]"#
+    );
+    assert_eq!(
+        locate_path(&automation, "0/1/0")?,
+        r#"> Window[Name="Calculator"]:nth(2) Text[Name="Display"]"#
+    );
+
+    Ok(())
+}
+
+#[test]
+fn locate_ordinal_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/calculator.json")?;
+
+    // Unnamed buttons are counted below their nearest anchor.
+    assert_eq!(
+        locate_path(&automation, "0/0/0/3")?,
+        r#"> Window[Name="Calculator"]:nth(1) Group[AutomationId="NumberPad"] Button:nth(4)"#
+    );
+    assert_eq!(
+        locate_path(&automation, "0/0/1/0")?,
+        r#"> Window[Name="Calculator"]:nth(1) Group[AutomationId="Memory"] Button:nth(1)"#
+    );
+    // Without an anchor, they're counted in the whole window.
+    assert_eq!(
+        locate_path(&automation, "0/0/2/0")?,
+        r#"> Window[Name="Calculator"]:nth(1) Button[Name="Two"]:nth(2)"#
+    );
+
+    Ok(())
+}
+
+#[test]
+fn locate_element_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+
+    assert_eq!(
+        IndexPath::of(&automation, &automation.get_focused_element()?)?.to_string(),
+        "0/0/1/0/0"
+    );
+    assert_eq!(
+        IndexPath::of(&automation, &automation.get_root_element()?)?.to_string(),
+        "0"
+    );
+
+    // The taskbar has a rectangle, but the buttons in it don't.
+    let element = automation.get_element_from_point(100, 1050)?;
+    assert_eq!(element.get_current_name()?, "Taskbar");
+    assert_eq!(IndexPath::of(&automation, &element)?.to_string(), "0/0");
+    let element = automation.get_element_from_point(100, 100)?;
+    assert_eq!(element.get_current_name()?, "Desktop 1");
+
+    // Every element's path leads back to it.
+    let walker = automation.create_tree_walker(&automation.get_raw_view_condition()?)?;
+    let desktop = automation.get_root_element()?;
+    for element in walker.descendants(&desktop) {
+        let element = element?;
+        let path = IndexPath::of(&automation, &element)?;
+        assert!(automation.compare_elements(&path.resolve(&walker, &desktop)?, &element)?);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,209 @@
+use uiac::{
+    backend::{Automation as _, Element as _, TreeWalker as _},
+    breadcrumb,
+    memory::{Automation, Element},
+    IndexPath, UiacResult, View,
+};
+
+fn names(elements: impl Iterator<Item = UiacResult<Element>>) -> UiacResult<Vec<String>> {
+    elements
+        .map(|element| element?.get_current_name())
+        .collect()
+}
+
+#[test]
+fn children_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+    let walker = View::Raw.walker(&automation)?;
+    let desktop = automation.get_root_element()?;
+
+    assert_eq!(
+        names(walker.children(&desktop))?,
+        ["Taskbar", "", "Program Manager"]
+    );
+
+    let taskbar = walker.children(&desktop).next().unwrap()?;
+    let start = walker.children(&taskbar).next().unwrap()?;
+    assert_eq!(
+        names(walker.following_siblings(&start))?,
+        [
+            "",
+            "System Clock, 1:04 AM, \u{200e}1/\u{200e}22/\u{200e}2023",
+            "Show desktop"
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn descendants_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+    let walker = View::Control.walker(&automation)?;
+    let desktop = automation.get_root_element()?;
+
+    let clock = "System Clock, 1:04 AM, \u{200e}1/\u{200e}22/\u{200e}2023";
+    assert_eq!(
+        names(walker.descendants(&desktop))?,
+        [
+            "Taskbar",
+            "Start",
+            "Running applications",
+            "Firefox",
+            "",
+            clock,
+            "Show desktop",
+            "",
+            "",
+            "Program Manager",
+            "Desktop",
+            "Recycle Bin",
+        ]
+    );
+    assert_eq!(
+        names(walker.descendants_breadth_first(&desktop))?,
+        [
+            "Taskbar",
+            "",
+            "Program Manager",
+            "Start",
+            "Running applications",
+            clock,
+            "Show desktop",
+            "",
+            "Desktop",
+            "Firefox",
+            "",
+            "Recycle Bin",
+        ]
+    );
+
+    // Errors pass through the chain to the collect.
+    let names_with_s = walker
+        .descendants(&desktop)
+        .map(|element| element?.get_current_name())
+        .filter(|name| name.as_ref().map_or(true, |name| name.starts_with('S')))
+        .collect::<UiacResult<Vec<_>>>()?;
+    assert_eq!(names_with_s, ["Start", clock, "Show desktop"]);
+
+    Ok(())
+}
+
+#[test]
+fn ancestors_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+    let desktop = automation.get_root_element()?;
+
+    let raw_walker = View::Raw.walker(&automation)?;
+    let firefox = raw_walker
+        .descendants(&desktop)
+        .find(|element| {
+            element.as_ref().map_or(true, |element| {
+                element
+                    .get_current_name()
+                    .is_ok_and(|name| name == "Firefox")
+            })
+        })
+        .unwrap()?;
+    assert_eq!(
+        names(raw_walker.ancestors(&firefox))?,
+        ["Running applications", "", "Taskbar", "Desktop 1"]
+    );
+
+    // The control view skips the unnamed pane between the toolbar and the
+    // taskbar.
+    let control_walker = View::Control.walker(&automation)?;
+    assert_eq!(
+        names(control_walker.ancestors(&firefox))?,
+        ["Running applications", "Taskbar", "Desktop 1"]
+    );
+    assert_eq!(names(raw_walker.ancestors(&desktop))?, Vec::<String>::new());
+
+    Ok(())
+}
+
+#[test]
+fn element_navigation_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+    let walker = View::Control.walker(&automation)?;
+    let desktop = automation.get_root_element()?;
+    let firefox = "0/0/1/0/0"
+        .parse::<IndexPath>()?
+        .resolve(&View::Raw.walker(&automation)?, &desktop)?;
+    let start = "0/0/0"
+        .parse::<IndexPath>()?
+        .resolve(&View::Raw.walker(&automation)?, &desktop)?;
+
+    // Elements navigate as the walker would from them, whether given the
+    // walker or its view.
+    assert_eq!(
+        names(desktop.children(&walker))?,
+        names(walker.children(&desktop))?
+    );
+    assert_eq!(
+        names(desktop.children_in(&automation, View::Control)?)?,
+        ["Taskbar", "", "Program Manager"]
+    );
+    assert_eq!(
+        names(desktop.descendants(&walker))?,
+        names(walker.descendants(&desktop))?
+    );
+    assert_eq!(
+        names(desktop.descendants_in(&automation, View::Control)?)?,
+        names(walker.descendants(&desktop))?
+    );
+    assert_eq!(
+        names(desktop.descendants_breadth_first(&walker))?,
+        names(walker.descendants_breadth_first(&desktop))?
+    );
+    assert_eq!(
+        names(desktop.descendants_breadth_first_in(&automation, View::Control)?)?,
+        names(walker.descendants_breadth_first(&desktop))?
+    );
+    assert_eq!(
+        names(firefox.ancestors(&walker))?,
+        ["Running applications", "Taskbar", "Desktop 1"]
+    );
+    assert_eq!(
+        names(firefox.ancestors_in(&automation, View::Raw)?)?,
+        ["Running applications", "", "Taskbar", "Desktop 1"]
+    );
+    assert_eq!(
+        names(start.following_siblings(&walker))?,
+        names(walker.following_siblings(&start))?
+    );
+    assert_eq!(
+        names(start.following_siblings_in(&automation, View::Raw)?)?,
+        [
+            "",
+            "System Clock, 1:04 AM, \u{200e}1/\u{200e}22/\u{200e}2023",
+            "Show desktop"
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn breadcrumb_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+    let desktop = automation.get_root_element()?;
+    let firefox = "0/0/1/0/0"
+        .parse::<IndexPath>()?
+        .resolve(&View::Raw.walker(&automation)?, &desktop)?;
+
+    assert_eq!(
+        breadcrumb(&automation, &View::Raw.walker(&automation)?, &firefox)?,
+        r#"Pane "Desktop 1" > Pane "Taskbar" > Pane > ToolBar "Running applications" > Button "Firefox""#
+    );
+    assert_eq!(
+        breadcrumb(&automation, &View::Control.walker(&automation)?, &firefox)?,
+        r#"Pane "Desktop 1" > Pane "Taskbar" > ToolBar "Running applications" > Button "Firefox""#
+    );
+    assert_eq!(
+        breadcrumb(&automation, &View::Raw.walker(&automation)?, &desktop)?,
+        r#"Pane "Desktop 1""#
+    );
+
+    Ok(())
+}
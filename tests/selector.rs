@@ -0,0 +1,96 @@
+use uiac::{
+    backend::{Automation as _, Element as _},
+    memory::Automation,
+    selector::Selector,
+    UiacResult, View,
+};
+
+fn select(automation: &Automation, view: View, selector: &str) -> UiacResult<Vec<String>> {
+    let walker = automation.create_tree_walker(&view.condition(automation)?)?;
+    let selector: Selector = selector.parse()?;
+    selector
+        .find_all(&walker, &automation.get_root_element()?)?
+        .iter()
+        .map(|element| element.get_current_name())
+        .collect()
+}
+
+#[test]
+fn selector_combinator_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+
+    assert_eq!(
+        select(&automation, View::Raw, "Pane[Name=Taskbar] > Button")?,
+        [
+            "Start",
+            "System Clock, 1:04 AM, \u{200e}1/\u{200e}22/\u{200e}2023",
+            "Show desktop"
+        ]
+    );
+    assert_eq!(
+        select(&automation, View::Raw, "Pane[Name=Taskbar] Button")?,
+        [
+            "Start",
+            "Firefox",
+            "System Clock, 1:04 AM, \u{200e}1/\u{200e}22/\u{200e}2023",
+            "Show desktop"
+        ]
+    );
+    // The unnamed pane around the toolbar isn't in the control view.
+    assert_eq!(
+        select(
+            &automation,
+            View::Control,
+            "Pane[Name=Taskbar] > ToolBar > *"
+        )?,
+        ["Firefox", ""]
+    );
+    assert!(select(&automation, View::Raw, "Pane[Name=Taskbar] > ToolBar")?.is_empty());
+
+    // Nested contexts don't produce duplicates.
+    assert_eq!(select(&automation, View::Raw, "Pane Button")?.len(), 4);
+    assert_eq!(select(&automation, View::Raw, "> Button")?.len(), 0);
+
+    Ok(())
+}
+
+#[test]
+fn selector_attribute_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+
+    let names = |selector| select(&automation, View::Raw, selector);
+    assert_eq!(names("Button[Name^=S]")?.len(), 3);
+    assert_eq!(names("*[Name$=desktop]")?, ["Show desktop"]);
+    assert_eq!(names("*[Name*=Man]")?, ["Program Manager"]);
+    assert_eq!(names(r#"Button[Name/="^\w+ \w+$"]"#)?, ["Show desktop"]);
+    assert_eq!(names(r#"*[Name?="Program *"]"#)?, ["Program Manager"]);
+    assert_eq!(names(r#"*[Name~="SHOW DESKTOP"]"#)?, ["Show desktop"]);
+    assert_eq!(
+        names(r#"*[Name^~_=" program   MAN"]"#)?,
+        ["Program Manager"]
+    );
+    assert_eq!(names("*[AutomationId]")?, ["Start"]);
+    assert_eq!(names("[IsEnabled=true]")?, ["Start"]);
+    assert_eq!(names("*[ControlType=ListItem]")?, ["Recycle Bin"]);
+
+    Ok(())
+}
+
+#[test]
+fn selector_pseudo_class_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+
+    let names = |selector| select(&automation, View::Raw, selector);
+    assert_eq!(names("Button:first")?, ["Start"]);
+    assert_eq!(names("Button:last")?, ["Show desktop"]);
+    assert_eq!(names("Button:nth(2)")?, ["Firefox"]);
+    assert!(names("Button:nth(5)")?.is_empty());
+    assert_eq!(
+        names("Button:nth-child(1)")?,
+        ["Start", "Firefox"],
+        "the first child of the taskbar and of the toolbar"
+    );
+    assert_eq!(names("> Pane:nth(2) *:first")?, ["Desktop"]);
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use uiac::{
+    dump, memory::Automation, snapshot::Snapshot, DumpOptions, Format, Property, UiacResult,
+};
+
+fn dump_to_string(automation: &Automation, format: Format) -> UiacResult<String> {
+    let mut out = vec![];
+    dump(
+        automation,
+        &DumpOptions {
+            format,
+            properties: vec![
+                Property::Name,
+                Property::ControlType,
+                Property::AutomationId,
+            ],
+            ..DumpOptions::default()
+        },
+        &mut out,
+    )?;
+    Ok(String::from_utf8(out).unwrap())
+}
+
+#[test]
+fn snapshot_round_trip_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+    let json = dump_to_string(&automation, Format::Json)?;
+
+    let snapshot = Snapshot::from_json(&json)?;
+    assert_eq!(snapshot.root, "desktop");
+
+    let replayed = Automation::from_snapshot(snapshot);
+    assert_eq!(
+        dump_to_string(&replayed, Format::Text)?,
+        dump_to_string(&automation, Format::Text)?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn snapshot_deep_round_trip_test() -> UiacResult<()> {
+    // Well past serde_json's limit of 128 nested values, two to a level.
+    const DEPTH: usize = 1_000;
+
+    let json = format!(
+        r#"{}{{"name":"Leaf","control_type":"Text"}}{}"#,
+        r#"{"control_type":"Group","children":["#.repeat(DEPTH),
+        "]}".repeat(DEPTH)
+    );
+    let automation = Automation::from_json(&json)?;
+    let text = dump_to_string(&automation, Format::Text)?;
+    assert_eq!(text.lines().count(), DEPTH + 1);
+
+    let snapshot = Snapshot::from_json(&dump_to_string(&automation, Format::Json)?)?;
+    let replayed = Automation::from_snapshot(snapshot);
+    assert_eq!(dump_to_string(&replayed, Format::Text)?, text);
+
+    Ok(())
+}
@@ -0,0 +1,277 @@
+use uiac::{
+    backend::{Automation as _, Element as _},
+    find,
+    memory::Automation,
+    xpath::{XPath, XPathValue},
+    FindOptions, Format, UiacResult, View,
+};
+
+/// Evaluates an XPath, giving the names of any matched elements.
+fn evaluate(automation: &Automation, view: View, xpath: &str) -> UiacResult<XPathValue<String>> {
+    let walker = automation.create_tree_walker(&view.condition(automation)?)?;
+    let xpath: XPath = xpath.parse()?;
+    Ok(
+        match xpath.evaluate(&walker, &automation.get_root_element()?)? {
+            XPathValue::Elements(elements) => XPathValue::Elements(
+                elements
+                    .iter()
+                    .map(|element| element.get_current_name())
+                    .collect::<UiacResult<_>>()?,
+            ),
+            XPathValue::Attributes(values) => XPathValue::Attributes(values),
+            XPathValue::String(s) => XPathValue::String(s),
+            XPathValue::Number(n) => XPathValue::Number(n),
+            XPathValue::Bool(b) => XPathValue::Bool(b),
+        },
+    )
+}
+
+fn select(automation: &Automation, view: View, xpath: &str) -> UiacResult<Vec<String>> {
+    match evaluate(automation, view, xpath)? {
+        XPathValue::Elements(names) => Ok(names),
+        value => panic!("{xpath} gave {value:?}"),
+    }
+}
+
+#[test]
+fn xpath_path_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+
+    let names = |xpath| select(&automation, View::Raw, xpath);
+    assert_eq!(names("/Pane")?, ["Desktop 1"]);
+    assert_eq!(names("/Button")?.len(), 0);
+    assert_eq!(
+        names("/Pane/Pane[@Name='Taskbar']/Button")?,
+        [
+            "Start",
+            "System Clock, 1:04 AM, \u{200e}1/\u{200e}22/\u{200e}2023",
+            "Show desktop"
+        ]
+    );
+    assert_eq!(
+        names("//Pane[@Name='Taskbar']//Button[@AutomationId='StartButton']")?,
+        ["Start"]
+    );
+    assert_eq!(names("//Pane//Button")?.len(), 4);
+    assert_eq!(names("//ToolBar/*")?, ["Firefox", ""]);
+    assert_eq!(names("//Custom/..")?, ["Running applications"]);
+    assert_eq!(names("//Custom/../.")?, ["Running applications"]);
+    assert_eq!(
+        names("//Window | //ListItem | //Window")?,
+        ["", "Recycle Bin"]
+    );
+    // Unions go in document order, whatever the depths of their members.
+    assert_eq!(
+        names("//ListItem | //Button | //Pane")?,
+        names("//*[self::ListItem or self::Button or self::Pane]")?
+    );
+
+    // The unnamed pane around the toolbar isn't in the control view.
+    assert_eq!(
+        select(
+            &automation,
+            View::Control,
+            "//Pane[@Name='Taskbar']/ToolBar"
+        )?,
+        ["Running applications"]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn xpath_axis_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+
+    let names = |xpath| select(&automation, View::Raw, xpath);
+    assert_eq!(
+        names("//Button[@Name='Firefox']/ancestor::*")?,
+        ["Desktop 1", "Taskbar", "", "Running applications"]
+    );
+    // Reverse axes count positions from the context node.
+    assert_eq!(
+        names("//Button[@Name='Firefox']/ancestor::*[3]")?,
+        ["Taskbar"]
+    );
+    assert_eq!(
+        names("//ToolBar/ancestor-or-self::Pane")?,
+        ["Desktop 1", "Taskbar", ""]
+    );
+    assert_eq!(
+        names("//Button[@Name='Show desktop']/preceding-sibling::*[1]")?,
+        ["System Clock, 1:04 AM, \u{200e}1/\u{200e}22/\u{200e}2023"]
+    );
+    assert_eq!(
+        names("//Button[@Name='Start']/following-sibling::Button")?,
+        [
+            "System Clock, 1:04 AM, \u{200e}1/\u{200e}22/\u{200e}2023",
+            "Show desktop"
+        ]
+    );
+    assert_eq!(
+        names("//List/descendant-or-self::*")?,
+        ["Desktop", "Recycle Bin"]
+    );
+    assert_eq!(names("//ListItem/self::List")?.len(), 0);
+
+    Ok(())
+}
+
+#[test]
+fn xpath_predicate_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+
+    let names = |xpath| select(&automation, View::Raw, xpath);
+    assert_eq!(names("//Pane[@Name='Taskbar']/Button[1]")?, ["Start"]);
+    assert_eq!(
+        names("//Pane[@Name='Taskbar']/Button[last()]")?,
+        ["Show desktop"]
+    );
+    assert_eq!(
+        names("//Pane[@Name='Taskbar']/*[position() > 1 and position() < last()]")?,
+        [
+            "",
+            "System Clock, 1:04 AM, \u{200e}1/\u{200e}22/\u{200e}2023"
+        ]
+    );
+    // "//Button[1]" is the first button child of each parent.
+    assert_eq!(names("//Button[1]")?, ["Start", "Firefox"]);
+    assert_eq!(
+        names("(//Button)[1]").ok(),
+        None,
+        "filter expressions aren't supported"
+    );
+    assert_eq!(names("//Button[@IsEnabled='True']")?, ["Start"]);
+    assert_eq!(names("//Button[@IsEnabled=true()]")?, ["Start"]);
+    assert_eq!(names("//*[@HasKeyboardFocus='True']")?, ["Firefox"]);
+    assert_eq!(names("//*[@ControlType='ListItem']")?, ["Recycle Bin"]);
+    assert_eq!(names("//*[@AutomationId]")?, ["Start"]);
+    assert_eq!(
+        names("//Button[contains(@Name, 'desk')]")?,
+        ["Show desktop"]
+    );
+    assert_eq!(
+        names("//*[starts-with(@ClassName, 'Prog')]")?,
+        ["Program Manager"]
+    );
+    assert_eq!(
+        names(r"//Button[matches(@Name, '^\w+ \w+$')]")?,
+        ["Show desktop"]
+    );
+    assert_eq!(
+        names("//*[matches(@Name, '^program', 'i')]")?,
+        ["Program Manager"]
+    );
+    assert!(names("//*[matches(@Name, 'a', 'q')]").is_err());
+    assert!(names("//*[matches(@Name, '(')]").is_err());
+    assert_eq!(names("//Button[not(@Name != 'Start')]")?, ["Start"]);
+    assert_eq!(names("//Pane[count(*) = 4]")?, ["Taskbar"]);
+    assert_eq!(names("//*[name() = 'List']")?, ["Desktop"]);
+    assert_eq!(names("//*[. = 'Desktop']")?, ["Desktop"]);
+    assert_eq!(names("//Pane[Button/@Name = 'Start']")?, ["Taskbar"]);
+    assert_eq!(
+        names("//*[string-length(@Name) = 0 or @Name = 'Firefox']")?,
+        ["", "Firefox", "", "", ""]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn xpath_unknown_control_type_test() -> UiacResult<()> {
+    let automation = Automation::from_json(
+        r#"{
+            "name": "Desktop 1",
+            "control_type": "Pane",
+            "children": [
+                {"name": "x", "control_type": "Unknown(50100)"},
+                {"name": "y", "control_type": "Unknown(50101)"}
+            ]
+        }"#,
+    )?;
+
+    let names = |xpath| select(&automation, View::Raw, xpath);
+    assert_eq!(names("//Unknown(50101)")?, ["y"]);
+    assert_eq!(names("/Pane/Unknown(50100)[@Name='x']")?, ["x"]);
+    assert_eq!(names("//*[@ControlType='Unknown(50100)']")?, ["x"]);
+
+    Ok(())
+}
+
+#[test]
+fn xpath_value_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+
+    let value = |xpath| evaluate(&automation, View::Raw, xpath);
+    assert_eq!(
+        value("//Pane/@ClassName")?,
+        XPathValue::Attributes(vec!["Shell_TrayWnd".to_owned(), "Progman".to_owned()])
+    );
+    assert_eq!(value("count(//Button)")?, XPathValue::Number(4.0));
+    assert_eq!(
+        value("normalize-space('  Show   desktop ')")?,
+        XPathValue::String("Show desktop".to_owned())
+    );
+    assert_eq!(
+        value("string(//Pane/@Name)")?,
+        XPathValue::String("Desktop 1".to_owned())
+    );
+    assert_eq!(value("//Edit or //List")?, XPathValue::Bool(true));
+    assert_eq!(value("count(//Pane) >= 4")?, XPathValue::Bool(true));
+    assert_eq!(value("number('12') < 3")?, XPathValue::Bool(false));
+
+    Ok(())
+}
+
+#[test]
+fn xpath_find_test() -> UiacResult<()> {
+    let automation = Automation::from_file("tests/fixtures/desktop.json")?;
+
+    let run = |xpath: &str, format, first, count| {
+        let mut out = vec![];
+        find(
+            &automation,
+            &FindOptions {
+                format,
+                xpath: Some(xpath.parse()?),
+                first,
+                count,
+                ..FindOptions::default()
+            },
+            &mut out,
+        )?;
+        Ok::<_, uiac::UiacError>(String::from_utf8(out).unwrap())
+    };
+
+    assert_eq!(
+        run(
+            "//Button[@AutomationId='StartButton']",
+            Format::Text,
+            false,
+            false
+        )?,
+        "│ Name = \"Start\", ControlType = Button\n"
+    );
+    assert_eq!(run("//Button", Format::Text, false, true)?, "4\n");
+    assert_eq!(
+        run("//Button", Format::Text, true, false)?,
+        "│ Name = \"Start\", ControlType = Button\n"
+    );
+    assert!(run("//Edit", Format::Text, true, false).is_err());
+    assert_eq!(
+        run("//Pane/@ClassName", Format::Text, false, false)?,
+        "Shell_TrayWnd\nProgman\n"
+    );
+    assert_eq!(run("//Pane/@ClassName", Format::Text, false, true)?, "2\n");
+    assert_eq!(
+        run("//Pane/@ClassName", Format::Json, true, false)?,
+        "[\n  \"Shell_TrayWnd\"\n]\n"
+    );
+    assert_eq!(run("count(//Button)", Format::Text, false, false)?, "4\n");
+    assert_eq!(
+        run("count(//Button) > 10", Format::Json, false, false)?,
+        "false\n"
+    );
+
+    Ok(())
+}